@@ -19,6 +19,24 @@ quick_error!{
         InvalidEnumValue {
             description("decoder: invalid enum value")
         }
+        MisMatchArrayLengthErr {
+            description("decoder: mismatch array length")
+        }
+        ValueOutOfRange {
+            description("decoder: value out of range")
+        }
+        NegativeLengthErr {
+            description("decoder: negative length")
+        }
+        UnbalancedStructErr {
+            description("decoder: unbalanced struct begin/end")
+        }
+        TrailingDataErr {
+            description("decoder: trailing data after value")
+        }
+        TooDeepErr {
+            description("decoder: nesting too deep")
+        }
         FieldNotFoundErr(desc: String) {
             description(desc)
         }
@@ -31,6 +49,17 @@ quick_error!{
         UnsupportTupVersionErr {
             description("decoder: Unsupport protocol version")
         }
+        TupAmbiguousTypeErr(desc: String) {
+            description(desc)
+        }
+        InvalidPacketLengthErr {
+            description("decoder: invalid packet length")
+        }
+        ReEncodeErr(err: EncodeErr) {
+            from()
+            description("decoder: re-encode error")
+            display("decoder: re-encode error, {}", err)
+        }
     }
 }
 
@@ -55,6 +84,9 @@ quick_error!{
         DataTooBigErr {
             description("encoder: data bigger than 4294967295 bytes")
         }
+        ValueOutOfRange {
+            description("encoder: value out of range")
+        }
         UnknownTarsTypeErr{
             description("encoder: unknown tars type")
         }
@@ -63,3 +95,31 @@ quick_error!{
         }
     }
 }
+
+quick_error!{
+    #[derive(Debug, PartialEq)]
+    pub enum JsonErr{
+        Decode(err: DecodeErr) {
+            from()
+            description("json: tars decode error")
+            display("json: {}", err)
+        }
+        Encode(err: EncodeErr) {
+            from()
+            description("json: tars encode error")
+            display("json: {}", err)
+        }
+        InvalidJsonErr(desc: String) {
+            description("json: invalid json value")
+            display("json: invalid json value, {}", desc)
+        }
+        OutOfRangeErr(desc: String) {
+            description("json: value out of range")
+            display("json: value out of range, {}", desc)
+        }
+        UnknownFieldErr(name: String) {
+            description("json: unknown field")
+            display("json: unknown field {}", name)
+        }
+    }
+}
@@ -6,6 +6,10 @@ extern crate bytes;
 
 #[macro_use]
 extern crate quick_error;
+#[cfg(feature = "json")]
+extern crate base64;
+#[cfg(feature = "json")]
+extern crate serde_json;
 
 pub mod errors;
 
@@ -13,16 +17,39 @@ pub mod tars_type;
 
 pub mod tars_trait;
 
+pub mod tars_class_name;
+
 pub mod tars_decoder;
+pub mod tars_reader;
 pub mod tars_encoder;
+pub mod tars_validator;
+pub mod tars_value;
+pub mod tars_diff;
+pub mod tars_patcher;
+pub mod tars_display;
+#[cfg(feature = "json")]
+pub mod tars_json;
 
 pub mod tup_uni_attribute;
+pub mod tup_uni_packet;
+
+pub use tars_validator::{validate, ValidateErr, ValidateOptions};
 
 pub mod prelude {
     pub use errors::*;
     pub use tars_decoder::*;
     pub use tars_encoder::*;
+    pub use tars_reader::*;
     pub use tars_trait::*;
+    pub use tars_class_name::*;
     pub use tars_type::*;
+    pub use tars_validator::*;
+    pub use tars_value::*;
+    pub use tars_diff::*;
+    #[cfg(feature = "json")]
+    pub use tars_json::*;
+    pub use tars_patcher::*;
+    pub use tars_display::*;
     pub use tup_uni_attribute::*;
+    pub use tup_uni_packet::*;
 }
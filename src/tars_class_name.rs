@@ -0,0 +1,269 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
+
+// 类型名解析后的形式，如 map<string,list<int32>>
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TypeName {
+    name: String,
+    args: Vec<TypeName>,
+}
+
+impl TypeName {
+    fn parse(s: &str) -> Option<TypeName> {
+        let (t, rest) = Self::parse_one(s)?;
+        if rest.trim().is_empty() {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    fn parse_one(s: &str) -> Option<(TypeName, &str)> {
+        let end = s.find(&['<', ',', '>'][..]).unwrap_or(s.len());
+        let name = s[..end].trim().to_string();
+        if name.is_empty() {
+            return None;
+        }
+        let mut rest = &s[end..];
+        let mut args = vec![];
+        if let Some(r) = rest.strip_prefix('<') {
+            rest = r;
+            loop {
+                let (arg, r) = Self::parse_one(rest)?;
+                args.push(arg);
+                let r = r.trim_start();
+                if let Some(r) = r.strip_prefix(',') {
+                    rest = r;
+                } else {
+                    rest = r.strip_prefix('>')?;
+                    break;
+                }
+            }
+        }
+        Some((TypeName { name, args }, rest))
+    }
+
+    // 返回是否有改动
+    fn rename<'t, F>(&mut self, lookup: &F) -> bool
+    where
+        F: Fn(&str) -> Option<&'t str>,
+    {
+        let mut renamed = false;
+        if let Some(to) = lookup(&self.name) {
+            self.name = to.to_string();
+            renamed = true;
+        }
+        for arg in self.args.iter_mut() {
+            renamed |= arg.rename(lookup);
+        }
+        renamed
+    }
+}
+
+impl fmt::Display for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.args.is_empty() {
+            write!(f, "<")?;
+            for (i, arg) in self.args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", arg)?;
+            }
+            write!(f, ">")?;
+        }
+        Ok(())
+    }
+}
+
+// 各语言对同一类型的不同写法，统一到 C++ 的写法后比较
+const ALIASES: &[(&str, &str)] = &[
+    ("List", "list"),
+    ("vector", "list"),
+    ("array", "list"),
+    ("byte", "char"),
+    ("int8", "char"),
+    ("boolean", "bool"),
+    ("int16", "short"),
+    ("int", "int32"),
+    ("long", "int64"),
+    ("String", "string"),
+    ("Map", "map"),
+];
+
+fn alias_of(name: &str) -> Option<&'static str> {
+    ALIASES
+        .iter()
+        .find(|&&(from, _)| from == name)
+        .map(|&(_, to)| to)
+}
+
+// 写入 TupComplex 时使用的类型名写法
+// 在 ClassName 生成的类型名上逐个替换类型名中的各部分，struct 名不受影响
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct ClassNameDialect {
+    renames: BTreeMap<String, String>,
+}
+
+impl ClassNameDialect {
+    // 与 ClassName 一致: Vec<T> 为 List<T>，Bytes 为 list<byte>
+    pub fn rust() -> Self {
+        ClassNameDialect::default()
+    }
+
+    // tars2cpp 的 Class<T>::name()，vector<T> 为 list<T>，vector<char> 为 list<char>
+    pub fn cpp() -> Self {
+        ClassNameDialect::rust()
+            .with_rename("List", "list")
+            .with_rename("byte", "char")
+    }
+
+    // Tars-Java 的 BasicClassTypeUtil，List<T> 与 T[] 为 list<T>，Byte 与 byte[] 为 char、list<char>
+    pub fn java() -> Self {
+        ClassNameDialect::rust()
+            .with_rename("List", "list")
+            .with_rename("byte", "char")
+    }
+
+    // @tars/stream 的 _classname，TarsStream.List(T) 为 list<T>，BinBuffer 为 list<char>
+    pub fn node() -> Self {
+        ClassNameDialect::rust()
+            .with_rename("List", "list")
+            .with_rename("byte", "char")
+    }
+
+    pub fn with_rename(mut self, from: &str, to: &str) -> Self {
+        self.renames.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    // 无需替换或无法解析的类型名原样返回，不分配新的字符串
+    pub fn translate<'a>(&self, class_name: &'a str) -> Cow<'a, str> {
+        if self.renames.is_empty() {
+            return Cow::Borrowed(class_name);
+        }
+        match TypeName::parse(class_name) {
+            Some(mut t) => {
+                if t.rename(&|name: &str| self.renames.get(name).map(|to| to.as_str())) {
+                    Cow::Owned(t.to_string())
+                } else {
+                    Cow::Borrowed(class_name)
+                }
+            }
+            None => Cow::Borrowed(class_name),
+        }
+    }
+}
+
+// 去掉空白并统一各语言的别名，结果相同的类型名视为同一类型
+// 如 List<char>、list<byte>、vector<char> 均为 list<char>
+pub fn normalize_class_name(class_name: &str) -> String {
+    match TypeName::parse(class_name) {
+        Some(mut t) => {
+            t.rename(&alias_of);
+            t.to_string()
+        }
+        None => class_name.to_string(),
+    }
+}
+
+// 按统一写法拆分为类型名与各类型参数，如 Map<String, List<int>> 为 ("map", ["string", "list<int32>"])
+pub fn split_class_name(class_name: &str) -> Option<(String, Vec<String>)> {
+    let t = TypeName::parse(&normalize_class_name(class_name))?;
+    Some((t.name, t.args.iter().map(|arg| arg.to_string()).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use std::collections::BTreeMap;
+    use tars_trait::ClassName;
+
+    #[test]
+    fn test_translate() {
+        let name = BTreeMap::<String, Vec<Bytes>>::_class_name();
+        assert_eq!(name, "map<string,List<list<byte>>>");
+        assert_eq!(ClassNameDialect::rust().translate(&name), name);
+        assert_eq!(
+            ClassNameDialect::cpp().translate(&name),
+            "map<string,list<list<char>>>"
+        );
+        assert_eq!(
+            ClassNameDialect::java().translate("List<Test.Foo>"),
+            "list<Test.Foo>"
+        );
+        assert_eq!(
+            ClassNameDialect::node().translate(&name),
+            "map<string,list<list<char>>>"
+        );
+        assert_eq!(
+            ClassNameDialect::rust()
+                .with_rename("List", "vector")
+                .translate("List<int32>"),
+            "vector<int32>"
+        );
+        assert_eq!(ClassNameDialect::cpp().translate("map<a"), "map<a");
+    }
+
+    #[test]
+    fn test_normalize() {
+        for name in ["List<char>", "list<byte>", "vector<char>", "list< char >"].iter() {
+            assert_eq!(normalize_class_name(name), "list<char>");
+        }
+        assert_eq!(
+            normalize_class_name("Map<String, List<int>>"),
+            "map<string,list<int32>>"
+        );
+        assert_eq!(normalize_class_name("Test.Foo"), "Test.Foo");
+        assert_eq!(normalize_class_name("list<short>"), "list<short>");
+        assert_eq!(
+            split_class_name("Map<String, List<int>>"),
+            Some((
+                "map".to_string(),
+                vec!["string".to_string(), "list<int32>".to_string()]
+            ))
+        );
+        assert_eq!(
+            split_class_name("int32"),
+            Some(("int32".to_string(), vec![]))
+        );
+        assert_eq!(split_class_name("list<"), None);
+    }
+
+    fn is_borrowed(name: Cow<str>) -> bool {
+        match name {
+            Cow::Borrowed(_) => true,
+            Cow::Owned(_) => false,
+        }
+    }
+
+    #[test]
+    fn test_class_name_cow() {
+        // 基础类型不分配
+        assert!(is_borrowed(i32::_class_name_cow()));
+        assert!(is_borrowed(Bytes::_class_name_cow()));
+        assert!(is_borrowed(<&str>::_class_name_cow()));
+        assert!(is_borrowed(<[u8; 4]>::_class_name_cow()));
+        assert_eq!(<&str>::_class_name_cow(), "string");
+        assert_eq!(<[u8; 4]>::_class_name_cow(), "list<byte>");
+        let name = BTreeMap::<String, Vec<i64>>::_class_name_cow();
+        assert_eq!(name, "map<string,List<int64>>");
+        assert!(!is_borrowed(name));
+        assert_eq!(<[u8]>::_class_name_cow(), "List<short>");
+    }
+
+    #[test]
+    fn test_translate_borrowed() {
+        let cpp = ClassNameDialect::cpp();
+        // 不需要替换时不分配
+        assert!(is_borrowed(cpp.translate("map<string,int32>")));
+        let rust = ClassNameDialect::rust();
+        assert!(is_borrowed(rust.translate("List<int32>")));
+        let name = cpp.translate("List<int32>");
+        assert_eq!(name, "list<int32>");
+        assert!(!is_borrowed(name));
+    }
+}
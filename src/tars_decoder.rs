@@ -1,16 +1,24 @@
 use bytes::{Buf, Bytes, IntoBuf};
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hash};
 use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
 
 use errors::DecodeErr;
+use tars_reader::{TarsReader, TarsVisitor};
 use tars_trait::{EnumFromI32, EnumToI32, StructFromTars};
-use tars_type::TarsTypeMark;
 use tars_type::TarsTypeMark::*;
+use tars_type::{SimpleList, TarsTypeMark};
+use tars_value::{PathSegment, TarsValue};
 
 #[derive(Debug)]
 pub struct TarsDecoder {
     buf: Bytes,
     pos: usize,
+    lenient_numeric: bool,
 }
 #[derive(Debug)]
 pub struct Head {
@@ -24,9 +32,18 @@ impl TarsDecoder {
         TarsDecoder {
             buf: Bytes::new(),
             pos: 0,
+            lenient_numeric: false,
         }
     }
 
+    // 数值兼容模式，与 C++/Java 的 TarsInputStream 行为一致：
+    // read_double 接受 float 与 int8/int16/int32，read_float 接受 int8/int16
+    // 只接受可以精确转换的宽度 (按 head 中的类型判断)，double 读为 float、int64 读为 double 等依然报错
+    pub fn with_lenient_numeric(mut self, lenient: bool) -> Self {
+        self.lenient_numeric = lenient;
+        self
+    }
+
     pub fn individual_decode<T>(buf: &Bytes) -> Result<T, DecodeErr>
     where
         T: DecodeTars,
@@ -35,6 +52,94 @@ impl TarsDecoder {
         T::_decode(&mut decoder, 0)
     }
 
+    // 从当前位置开始遍历剩余 buffer
+    pub fn accept<V>(&mut self, visitor: &mut V) -> Result<(), DecodeErr>
+    where
+        V: TarsVisitor,
+    {
+        let reader = TarsReader::new(&self.buf[self.pos..]);
+        reader.accept(visitor)?;
+        self.pos = self.buf.len();
+        Ok(())
+    }
+
+    // 按路径读取单个 field，其余部分只做 skip，不改变当前位置
+    // 第一段须为顶层 tag，Tag 用于 struct，Index 用于 list，Key 按字面值匹配 map key
+    pub fn project(&mut self, path: &[PathSegment]) -> Result<TarsValue, DecodeErr> {
+        let field = self.project_bytes(path)?;
+        TarsValue::from_field(&field).map(|(_, v)| v)
+    }
+
+    // 返回目标 field 的完整字节 (含 head)
+    pub fn project_bytes(&mut self, path: &[PathSegment]) -> Result<Bytes, DecodeErr> {
+        let before_pos = self.current_pos();
+        let result = self.seek_path(path).and_then(|head| self.take_field_bytes(head));
+        self.set_pos(before_pos)?;
+        result
+    }
+
+    fn seek_path(&mut self, path: &[PathSegment]) -> Result<Head, DecodeErr> {
+        let mut head = match path.first() {
+            Some(PathSegment::Tag(tag)) => self.skip_to_tag(*tag)?,
+            _ => return Err(DecodeErr::TarsTagNotFoundErr),
+        };
+        for seg in path[1..].iter() {
+            head = match (seg, &head.tars_type) {
+                (PathSegment::Tag(tag), EnStructBegin) => self.seek_struct_tag(*tag)?,
+                (PathSegment::Index(idx), EnList) => self.seek_list_index(*idx)?,
+                (PathSegment::Key(key), EnMaps) => self.seek_map_key(key)?,
+                _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+            };
+        }
+        Ok(head)
+    }
+
+    // 只在当前 struct 内查找，遇到 EnStructEnd 即停止
+    fn seek_struct_tag(&mut self, tag: u8) -> Result<Head, DecodeErr> {
+        loop {
+            let head = self.take_head()?;
+            if head.tars_type == EnStructEnd {
+                return Err(DecodeErr::TarsTagNotFoundErr);
+            } else if head.tag == tag {
+                return Ok(head);
+            } else {
+                self.skip_field(head.tars_type)?;
+            }
+        }
+    }
+
+    fn seek_list_index(&mut self, idx: usize) -> Result<Head, DecodeErr> {
+        let size = self.take_size()?;
+        if idx >= size {
+            return Err(DecodeErr::TarsTagNotFoundErr);
+        }
+        for _ in 0..idx {
+            let head = self.take_head()?;
+            self.skip_field(head.tars_type)?;
+        }
+        self.take_head()
+    }
+
+    fn seek_map_key(&mut self, key: &TarsValue) -> Result<Head, DecodeErr> {
+        let size = self.take_size()?;
+        for _ in 0..size {
+            let key_head = self.take_head()?;
+            let key_field = self.take_field_bytes(key_head)?;
+            let (_, k) = TarsValue::from_field(&key_field)?;
+            let value_head = self.take_head()?;
+            if k == *key {
+                return Ok(value_head);
+            }
+            self.skip_field(value_head.tars_type)?;
+        }
+        Err(DecodeErr::TarsTagNotFoundErr)
+    }
+
+    // list/map 长度
+    fn take_size(&mut self) -> Result<usize, DecodeErr> {
+        self.with_reader(|reader| reader.take_len())
+    }
+
     #[inline]
     fn return_error_if_required_not_found<T>(
         e: DecodeErr,
@@ -73,15 +178,6 @@ impl TarsDecoder {
         }
     }
 
-    fn advance(&mut self, cnt: usize) -> Result<(), DecodeErr> {
-        if self.remaining() < cnt {
-            Err(DecodeErr::NoEnoughDataErr)
-        } else {
-            self.pos += cnt;
-            Ok(())
-        }
-    }
-
     fn take_then_advance(&mut self, size: usize) -> Result<Bytes, DecodeErr> {
         if self.remaining() < size {
             Err(DecodeErr::NoEnoughDataErr)
@@ -116,117 +212,98 @@ impl TarsDecoder {
         }
     }
 
-    fn take_head(&mut self) -> Result<Head, DecodeErr> {
-        if self.remaining() < 1 {
-            Err(DecodeErr::NoEnoughDataErr)
-        } else {
-            let mut buf = self.take_then_advance(1)?.into_buf();
-            let b = buf.get_u8();
-            let tars_type = b & 0x0f;
-            let mut tag = (b & 0xf0) >> 4;
-            let len = if tag < 15 {
-                1
-            } else {
-                let mut buf = self.take_then_advance(1)?.into_buf();
-                tag = buf.get_u8();
-                2
-            };
-            Ok(Head {
-                tag,
-                len,
-                tars_type: TarsTypeMark::from(tars_type),
-            })
-        }
-    }
-
-    fn skip_field(&mut self, tars_type: TarsTypeMark) -> Result<(), DecodeErr> {
-        match tars_type {
-            EnInt8 => self.advance(1),
-            EnInt16 => self.advance(2),
-            EnInt32 => self.advance(4),
-            EnInt64 => self.advance(8),
-            EnFloat => self.advance(4),
-            EnDouble => self.advance(8),
-            EnString1 => self.skip_string1_field(),
-            EnString4 => self.skip_string4_field(),
-            EnMaps => self.skip_map_field(),
-            EnList => self.skip_list_field(),
-            EnStructBegin => self.skip_struct_field(),
-            EnStructEnd => Ok(()),
-            EnZero => Ok(()),
-            EnSimplelist => self.skip_simple_list_field(),
-        }
+    // head、长度与跳过 field 的解析均与 TarsReader 共用，仅在成功时前进
+    fn with_reader<T, F>(&mut self, f: F) -> Result<T, DecodeErr>
+    where
+        F: FnOnce(&mut TarsReader) -> Result<T, DecodeErr>,
+    {
+        let mut reader = TarsReader::with_position(&self.buf, self.pos);
+        let result = f(&mut reader)?;
+        self.pos = reader.position();
+        Ok(result)
     }
 
-    fn skip_string1_field(&mut self) -> Result<(), DecodeErr> {
-        let mut buf = self.take_then_advance(1)?.into_buf();
-        let size = buf.get_u8() as usize;
-        self.advance(size)
+    // 读取整数类型的 field 内容，EnZero 读为 0
+    fn take_integer(&mut self, tars_type: TarsTypeMark) -> Result<i64, DecodeErr> {
+        self.with_reader(|reader| reader.take_int(&tars_type))
     }
 
-    fn skip_string4_field(&mut self) -> Result<(), DecodeErr> {
-        let mut buf = self.take_then_advance(4)?.into_buf();
-        let size = buf.get_u32_be() as usize;
-        self.advance(size)
+    fn take_head(&mut self) -> Result<Head, DecodeErr> {
+        let before_pos = self.current_pos();
+        let (tag, tars_type) = self.with_reader(|reader| reader.take_head())?;
+        Ok(Head {
+            tag,
+            tars_type,
+            len: (self.current_pos() - before_pos) as u8,
+        })
     }
 
-    fn skip_map_field(&mut self) -> Result<(), DecodeErr> {
-        let ele_size = self.read_int32(0, true, 0)? as usize;
-        for _ in 0..ele_size * 2 {
-            let head = self.take_head()?;
-            self.skip_field(head.tars_type)?;
-        }
-        Ok(())
+    fn skip_field(&mut self, tars_type: TarsTypeMark) -> Result<(), DecodeErr> {
+        self.with_reader(|reader| reader.skip_value(tars_type))
     }
 
-    fn skip_list_field(&mut self) -> Result<(), DecodeErr> {
-        let ele_size = self.read_int32(0, true, 0)? as usize;
-        for _ in 0..ele_size {
-            let head = self.take_head()?;
-            self.skip_field(head.tars_type)?;
-        }
-        Ok(())
+    // 当前 field 的完整字节 (含 head)，调用前 head 已被读取
+    fn take_field_bytes(&mut self, head: Head) -> Result<Bytes, DecodeErr> {
+        let start = self.current_pos() - head.len as usize;
+        self.skip_field(head.tars_type)?;
+        Ok(self.buf.slice(start, self.current_pos()))
     }
 
-    fn skip_simple_list_field(&mut self) -> Result<(), DecodeErr> {
-        let _head = self.take_head()?; // consume header (list type)
+    fn take_list_elements<T>(&mut self) -> Result<Vec<T>, DecodeErr>
+    where
+        T: DecodeTars,
+    {
         let size = self.read_int32(0, true, 0)? as usize;
-        self.advance(size)
+        let mut v = vec![];
+        for _ in 0..size {
+            let ele = T::_decode(self, 0)?;
+            v.push(ele);
+        }
+        Ok(v)
     }
 
-    fn skip_struct_field(&mut self) -> Result<(), DecodeErr> {
-        let mut head = self.take_head()?;
-        loop {
-            match head.tars_type {
-                EnStructEnd => break,
-                _ => {
-                    self.skip_field(head.tars_type)?;
-                    head = self.take_head()?;
-                }
+    fn take_simple_list(&mut self) -> Result<Bytes, DecodeErr> {
+        let head = self.take_head()?;
+        match head.tars_type {
+            EnInt8 | EnInt16 | EnInt32 => {
+                let size = self.read_int32(0, true, 0)? as usize;
+                self.take_then_advance(size)
             }
+            _ => Err(DecodeErr::WrongSimpleListTarsTypeErr),
         }
-        Ok(())
     }
 }
 
 impl<'a> From<&'a [u8]> for TarsDecoder {
     fn from(buf: &'a [u8]) -> Self {
         let b = Bytes::from(buf);
-        TarsDecoder { buf: b, pos: 0 }
+        TarsDecoder {
+            buf: b,
+            pos: 0,
+            lenient_numeric: false,
+        }
     }
 }
 
 impl<'a> From<&'a Bytes> for TarsDecoder {
     fn from(buf: &'a Bytes) -> Self {
         let b = buf.clone();
-        TarsDecoder { buf: b, pos: 0 }
+        TarsDecoder {
+            buf: b,
+            pos: 0,
+            lenient_numeric: false,
+        }
     }
 }
 
 impl From<Vec<u8>> for TarsDecoder {
     fn from(buf: Vec<u8>) -> Self {
         let b = Bytes::from(buf);
-        TarsDecoder { buf: b, pos: 0 }
+        TarsDecoder {
+            buf: b,
+            pos: 0,
+            lenient_numeric: false,
+        }
     }
 }
 
@@ -277,6 +354,13 @@ pub trait TarsDecodeNormalTrait {
         default_value: u32,
     ) -> Result<u32, DecodeErr>;
 
+    fn read_uint64(
+        &mut self,
+        tag: u8,
+        is_require: bool,
+        default_value: u64,
+    ) -> Result<u64, DecodeErr>;
+
     fn read_float(
         &mut self,
         tag: u8,
@@ -315,6 +399,17 @@ pub trait TarsDecodeNormalTrait {
         K: DecodeTars + Ord,
         V: DecodeTars;
 
+    fn read_hash_map<K, V, S>(
+        &mut self,
+        tag: u8,
+        is_require: bool,
+        default_value: HashMap<K, V, S>,
+    ) -> Result<HashMap<K, V, S>, DecodeErr>
+    where
+        K: DecodeTars + Eq + Hash,
+        V: DecodeTars,
+        S: BuildHasher + Default;
+
     fn read_enum<T>(&mut self, tag: u8, is_require: bool, default_value: T) -> Result<T, DecodeErr>
     where
         T: EnumFromI32 + EnumToI32;
@@ -424,26 +519,7 @@ impl TarsDecodeNormalTrait for TarsDecoder {
         default_value: i64,
     ) -> Result<i64, DecodeErr> {
         match self.skip_to_tag(tag) {
-            Ok(head) => match head.tars_type {
-                EnZero => Ok(0),
-                EnInt8 => {
-                    let mut buf = self.take_then_advance(1)?.into_buf();
-                    Ok(i64::from(buf.get_i8()))
-                }
-                EnInt16 => {
-                    let mut buf = self.take_then_advance(2)?.into_buf();
-                    Ok(i64::from(buf.get_i16_be()))
-                }
-                EnInt32 => {
-                    let mut buf = self.take_then_advance(4)?.into_buf();
-                    Ok(i64::from(buf.get_i32_be()))
-                }
-                EnInt64 => {
-                    let mut buf = self.take_then_advance(8)?.into_buf();
-                    Ok(buf.get_i64_be())
-                }
-                _ => Err(DecodeErr::MisMatchTarsTypeErr),
-            },
+            Ok(head) => self.take_integer(head.tars_type),
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
         }
     }
@@ -454,8 +530,8 @@ impl TarsDecodeNormalTrait for TarsDecoder {
         is_require: bool,
         default_value: u8,
     ) -> Result<u8, DecodeErr> {
-        self.read_int16(tag, is_require, default_value as i16)
-            .map(|i| i as u8)
+        let i = self.read_int16(tag, is_require, i16::from(default_value))?;
+        u8::try_from(i).map_err(|_| DecodeErr::ValueOutOfRange)
     }
 
     fn read_uint16(
@@ -464,8 +540,8 @@ impl TarsDecodeNormalTrait for TarsDecoder {
         is_require: bool,
         default_value: u16,
     ) -> Result<u16, DecodeErr> {
-        self.read_int32(tag, is_require, default_value as i32)
-            .map(|i| i as u16)
+        let i = self.read_int32(tag, is_require, i32::from(default_value))?;
+        u16::try_from(i).map_err(|_| DecodeErr::ValueOutOfRange)
     }
 
     fn read_uint32(
@@ -474,8 +550,21 @@ impl TarsDecodeNormalTrait for TarsDecoder {
         is_require: bool,
         default_value: u32,
     ) -> Result<u32, DecodeErr> {
-        self.read_int64(tag, is_require, default_value as i64)
-            .map(|i| i as u32)
+        let i = self.read_int64(tag, is_require, i64::from(default_value))?;
+        u32::try_from(i).map_err(|_| DecodeErr::ValueOutOfRange)
+    }
+
+    // u64 以 int64 编码，负数视为越界
+    fn read_uint64(
+        &mut self,
+        tag: u8,
+        is_require: bool,
+        default_value: u64,
+    ) -> Result<u64, DecodeErr> {
+        match self.read_int64(tag, true, 0) {
+            Ok(i) => u64::try_from(i).map_err(|_| DecodeErr::ValueOutOfRange),
+            Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
+        }
     }
 
     fn read_float(
@@ -491,6 +580,9 @@ impl TarsDecodeNormalTrait for TarsDecoder {
                     let mut buf = self.take_then_advance(4)?.into_buf();
                     Ok(buf.get_f32_be())
                 }
+                EnInt8 | EnInt16 if self.lenient_numeric => {
+                    Ok(self.take_integer(head.tars_type)? as f32)
+                }
                 _ => Err(DecodeErr::MisMatchTarsTypeErr),
             },
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
@@ -510,6 +602,13 @@ impl TarsDecodeNormalTrait for TarsDecoder {
                     let mut buf = self.take_then_advance(8)?.into_buf();
                     Ok(buf.get_f64_be())
                 }
+                EnFloat if self.lenient_numeric => {
+                    let mut buf = self.take_then_advance(4)?.into_buf();
+                    Ok(f64::from(buf.get_f32_be()))
+                }
+                EnInt8 | EnInt16 | EnInt32 if self.lenient_numeric => {
+                    Ok(self.take_integer(head.tars_type)? as f64)
+                }
                 _ => Err(DecodeErr::MisMatchTarsTypeErr),
             },
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
@@ -552,16 +651,7 @@ impl TarsDecodeNormalTrait for TarsDecoder {
     ) -> Result<Bytes, DecodeErr> {
         match self.skip_to_tag(tag) {
             Ok(head) => match head.tars_type {
-                EnSimplelist => {
-                    let head = self.take_head()?;
-                    match head.tars_type {
-                        EnInt8 | EnInt16 | EnInt32 => {
-                            let size = self.read_int32(0, true, 0)? as usize;
-                            self.take_then_advance(size)
-                        }
-                        _ => Err(DecodeErr::WrongSimpleListTarsTypeErr),
-                    }
-                }
+                EnSimplelist => self.take_simple_list(),
                 _ => Err(DecodeErr::MisMatchTarsTypeErr),
             },
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
@@ -596,6 +686,35 @@ impl TarsDecodeNormalTrait for TarsDecoder {
         }
     }
 
+    fn read_hash_map<K, V, S>(
+        &mut self,
+        tag: u8,
+        is_require: bool,
+        default_value: HashMap<K, V, S>,
+    ) -> Result<HashMap<K, V, S>, DecodeErr>
+    where
+        K: DecodeTars + Eq + Hash,
+        V: DecodeTars,
+        S: BuildHasher + Default,
+    {
+        match self.skip_to_tag(tag) {
+            Ok(head) => match head.tars_type {
+                EnMaps => {
+                    let size = self.read_int32(0, true, 0)? as usize;
+                    let mut m = HashMap::with_hasher(S::default());
+                    for _ in 0..size {
+                        let key = K::_decode(self, 0)?;
+                        let value = V::_decode(self, 1)?;
+                        m.insert(key, value);
+                    }
+                    Ok(m)
+                }
+                _ => Err(DecodeErr::MisMatchTarsTypeErr),
+            },
+            Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
+        }
+    }
+
     fn read_enum<T>(&mut self, tag: u8, is_require: bool, default_value: T) -> Result<T, DecodeErr>
     where
         T: EnumFromI32 + EnumToI32,
@@ -635,15 +754,26 @@ where
     ) -> Result<Vec<T>, DecodeErr> {
         match self.skip_to_tag(tag) {
             Ok(head) => match head.tars_type {
-                EnList => {
-                    let size = self.read_int32(0, true, 0)? as usize;
-                    let mut v = vec![];
-                    for _ in 0..size {
-                        let ele = T::_decode(self, 0)?;
-                        v.push(ele);
-                    }
-                    Ok(v)
-                }
+                EnList => self.take_list_elements(),
+                _ => Err(DecodeErr::MisMatchTarsTypeErr),
+            },
+            Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
+        }
+    }
+}
+
+// vector<unsigned char> 两种编码均可接受
+impl TarsDecodeListTrait<u8> for TarsDecoder {
+    fn read_list(
+        &mut self,
+        tag: u8,
+        is_require: bool,
+        default_value: Vec<u8>,
+    ) -> Result<Vec<u8>, DecodeErr> {
+        match self.skip_to_tag(tag) {
+            Ok(head) => match head.tars_type {
+                EnSimplelist => Ok(self.take_simple_list()?.to_vec()),
+                EnList => self.take_list_elements(),
                 _ => Err(DecodeErr::MisMatchTarsTypeErr),
             },
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
@@ -660,16 +790,8 @@ impl TarsDecodeListTrait<i8> for TarsDecoder {
     ) -> Result<Vec<i8>, DecodeErr> {
         match self.skip_to_tag(tag) {
             Ok(head) => match head.tars_type {
-                EnSimplelist => {
-                    let head = self.take_head()?;
-                    match head.tars_type {
-                        EnInt8 | EnInt16 | EnInt32 => {
-                            let size = self.read_int32(0, true, 0)? as usize;
-                            Ok(unsafe { mem::transmute(self.take_then_advance(size)?.to_vec()) })
-                        }
-                        _ => Err(DecodeErr::WrongSimpleListTarsTypeErr),
-                    }
-                }
+                EnSimplelist => Ok(unsafe { mem::transmute(self.take_simple_list()?.to_vec()) }),
+                EnList => self.take_list_elements(),
                 _ => Err(DecodeErr::MisMatchTarsTypeErr),
             },
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
@@ -686,16 +808,8 @@ impl TarsDecodeListTrait<bool> for TarsDecoder {
     ) -> Result<Vec<bool>, DecodeErr> {
         match self.skip_to_tag(tag) {
             Ok(head) => match head.tars_type {
-                EnSimplelist => {
-                    let head = self.take_head()?;
-                    match head.tars_type {
-                        EnInt8 | EnInt16 | EnInt32 => {
-                            let size = self.read_int32(0, true, 0)? as usize;
-                            Ok(unsafe { mem::transmute(self.take_then_advance(size)?.to_vec()) })
-                        }
-                        _ => Err(DecodeErr::WrongSimpleListTarsTypeErr),
-                    }
-                }
+                EnSimplelist => Ok(unsafe { mem::transmute(self.take_simple_list()?.to_vec()) }),
+                EnList => self.take_list_elements(),
                 _ => Err(DecodeErr::MisMatchTarsTypeErr),
             },
             Err(e) => TarsDecoder::return_error_if_required_not_found(e, is_require, default_value),
@@ -757,6 +871,12 @@ impl DecodeTars for u32 {
     }
 }
 
+impl DecodeTars for u64 {
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        decoder.read_uint64(tag, true, u64::default())
+    }
+}
+
 impl DecodeTars for f32 {
     fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
         decoder.read_float(tag, true, f32::default())
@@ -794,12 +914,155 @@ where
     }
 }
 
+impl<K, V, S> DecodeTars for HashMap<K, V, S>
+where
+    K: DecodeTars + Eq + Hash,
+    V: DecodeTars,
+    S: BuildHasher + Default,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        decoder.read_hash_map(tag, true, HashMap::default())
+    }
+}
+
+impl<T> DecodeTars for VecDeque<T>
+where
+    T: DecodeTars,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        decoder.read_list(tag, true, vec![]).map(VecDeque::from)
+    }
+}
+
+impl<T> DecodeTars for BTreeSet<T>
+where
+    T: DecodeTars + Ord,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        let v: Vec<T> = decoder.read_list(tag, true, vec![])?;
+        Ok(v.into_iter().collect())
+    }
+}
+
+impl<T, S> DecodeTars for HashSet<T, S>
+where
+    T: DecodeTars + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        let v: Vec<T> = decoder.read_list(tag, true, vec![])?;
+        Ok(v.into_iter().collect())
+    }
+}
+
 impl DecodeTars for Bytes {
     fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
         decoder.read_bytes(tag, true, Bytes::default())
     }
 }
 
+impl DecodeTars for TarsValue {
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        let head = decoder.skip_to_tag(tag)?;
+        let field = decoder.take_field_bytes(head)?;
+        TarsValue::from_field(&field).map(|(_, v)| v)
+    }
+}
+
+impl DecodeTars for SimpleList {
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        decoder.read_list(tag, true, vec![]).map(SimpleList)
+    }
+}
+
+// [u8; N] 与 [i8; N] 同 Vec 一样接受 simplelist 与 list 两种编码
+impl<T, const N: usize> DecodeTars for [T; N]
+where
+    T: DecodeTars,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        let v: Vec<T> = decoder.read_list(tag, true, vec![])?;
+        <[T; N]>::try_from(v).map_err(|_| DecodeErr::MisMatchArrayLengthErr)
+    }
+}
+
+macro_rules! impl_tuple_decode {
+    ($($name:ident: $tag:tt),+) => {
+        impl<$($name),+> StructFromTars for ($($name,)+)
+        where
+            $($name: DecodeTars),+
+        {
+            fn _decode_from(decoder: &mut TarsDecoder) -> Result<Self, DecodeErr> {
+                Ok(($($name::_decode(decoder, $tag)?,)+))
+            }
+        }
+
+        impl<$($name),+> DecodeTars for ($($name,)+)
+        where
+            $($name: DecodeTars),+
+        {
+            fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+                let head = decoder.skip_to_tag(tag)?;
+                match head.tars_type {
+                    EnStructBegin => Self::_decode_from(decoder),
+                    _ => Err(DecodeErr::MisMatchTarsTypeErr),
+                }
+            }
+        }
+    };
+}
+
+impl_tuple_decode!(A: 0);
+impl_tuple_decode!(A: 0, B: 1);
+impl_tuple_decode!(A: 0, B: 1, C: 2);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+impl_tuple_decode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
+
+impl<T> DecodeTars for Box<T>
+where
+    T: DecodeTars,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        T::_decode(decoder, tag).map(Box::new)
+    }
+}
+
+impl<T> DecodeTars for Rc<T>
+where
+    T: DecodeTars,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        T::_decode(decoder, tag).map(Rc::new)
+    }
+}
+
+impl<T> DecodeTars for Arc<T>
+where
+    T: DecodeTars,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        T::_decode(decoder, tag).map(Arc::new)
+    }
+}
+
+// 解码结果总是 Cow::Owned
+impl<'a, T> DecodeTars for Cow<'a, T>
+where
+    T: ToOwned + ?Sized,
+    T::Owned: DecodeTars,
+{
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        T::Owned::_decode(decoder, tag).map(Cow::Owned)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -807,6 +1070,7 @@ mod tests {
     use errors::DecodeErr;
     use std::collections::BTreeMap;
     use std::mem;
+    use tars_encoder::*;
 
     #[test]
     fn test_decode_simple_list() {
@@ -828,7 +1092,7 @@ mod tests {
         assert_eq!(list, result);
 
         let olist: Vec<bool> = de2.read_list(16, false, vec![]).unwrap();
-        assert_eq!(olist, vec![]);
+        assert_eq!(olist, Vec::<bool>::new());
 
         let olist2: Vec<i8> = de2.read_list(244, false, vec![1, 2]).unwrap();
         assert_eq!(olist2, vec![1, 2]);
@@ -837,6 +1101,27 @@ mod tests {
         assert_eq!(err, Err(DecodeErr::TarsTagNotFoundErr));
     }
 
+    #[test]
+    fn test_decode_list_either_form() {
+        let d = b"\x09\x00\x02\x00\x01\x01\x00\xff\x1d\x00\x00\x02\x01\xff";
+        let mut de = TarsDecoder::from(&d[..]);
+        let v: Vec<u8> = de.read_list(0, true, vec![]).unwrap();
+        assert_eq!(v, vec![1, 255]);
+        let v: Vec<u8> = de.read_list(1, true, vec![]).unwrap();
+        assert_eq!(v, vec![1, 255]);
+
+        let d = b"\x09\x00\x02\x00\x01\x00\xff\x1d\x00\x00\x02\x01\xff";
+        let mut de = TarsDecoder::from(&d[..]);
+        let v: Vec<i8> = de.read_list(0, true, vec![]).unwrap();
+        assert_eq!(v, vec![1, -1]);
+        let v: Vec<i8> = de.read_list(1, true, vec![]).unwrap();
+        assert_eq!(v, vec![1, -1]);
+
+        let mut de = TarsDecoder::from(&d[..]);
+        let v = SimpleList::_decode(&mut de, 1).unwrap();
+        assert_eq!(v, SimpleList(vec![1, 255]));
+    }
+
     #[test]
     fn test_decode_zero() {
         let mut de = TarsDecoder::from(&b"\x0c\x1c\x2c\x3c\x4c\x5c\xfc\xff\x9c\xac\xec"[..]);
@@ -984,6 +1269,121 @@ mod tests {
         assert_eq!(err, Err(DecodeErr::TarsTagNotFoundErr));
     }
 
+    #[test]
+    fn test_decode_hash_map() {
+        let b = b"\x48\x00\x02\x06\x05hello\x10\x20\x06\x05world\x10\x2a";
+        let mut de = TarsDecoder::from(&b[..]);
+        let map: HashMap<String, i32> = de.read_hash_map(4, true, HashMap::new()).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("hello"), Some(&32));
+        assert_eq!(map.get("world"), Some(&42));
+
+        let omap: HashMap<String, i32> = de.read_hash_map(5, false, HashMap::new()).unwrap();
+        assert_eq!(omap, HashMap::new());
+
+        let err: Result<HashMap<String, i32>, DecodeErr> =
+            de.read_hash_map(129, true, HashMap::new());
+        assert_eq!(err, Err(DecodeErr::TarsTagNotFoundErr));
+    }
+
+    #[test]
+    fn test_decode_set_and_deque() {
+        let b = b"\x09\x00\x03\x00\x02\x00\x01\x00\x02\x1d\x00\x00\x02\x01\x02";
+        let mut de = TarsDecoder::from(&b[..]);
+        let set = BTreeSet::<i32>::_decode(&mut de, 0).unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<i32>>(), vec![1, 2]);
+        let deque = VecDeque::<i8>::_decode(&mut de, 1).unwrap();
+        assert_eq!(deque, VecDeque::from(vec![1, 2]));
+
+        let mut de = TarsDecoder::from(&b[..]);
+        let hash_set = HashSet::<i32>::_decode(&mut de, 0).unwrap();
+        assert_eq!(hash_set.len(), 2);
+        assert!(hash_set.contains(&1) && hash_set.contains(&2));
+    }
+
+    #[test]
+    fn test_decode_smart_pointer() {
+        let d: [u8; 11] = [0x06, 0x07, b'f', b'o', b'o', b' ', b'b', b'a', b'r', 0x10, 0x2a];
+        let mut de = TarsDecoder::from(&d[..]);
+        let s: Cow<str> = Cow::_decode(&mut de, 0).unwrap();
+        assert_eq!(s, "foo bar");
+        let i: Box<i32> = Box::_decode(&mut de, 1).unwrap();
+        assert_eq!(*i, 42);
+
+        let mut de = TarsDecoder::from(&d[..]);
+        let s: Arc<String> = Arc::_decode(&mut de, 0).unwrap();
+        assert_eq!(s.as_str(), "foo bar");
+        let i: Rc<i8> = Rc::_decode(&mut de, 1).unwrap();
+        assert_eq!(*i, 42);
+    }
+
+    #[test]
+    fn test_decode_tuple() {
+        let d = b"\x2a\x00\x01\x16\x03foo\x2c\x0b\x3a\x0c\x0b";
+        let mut de = TarsDecoder::from(&d[..]);
+        let t: (i32, String, u8) = DecodeTars::_decode(&mut de, 2).unwrap();
+        assert_eq!(t, (1, "foo".to_string(), 0));
+        let t1: (i64,) = DecodeTars::_decode(&mut de, 3).unwrap();
+        assert_eq!(t1, (0,));
+
+        let mut de = TarsDecoder::from(&d[..]);
+        let err: Result<(String,), DecodeErr> = DecodeTars::_decode(&mut de, 2);
+        assert_eq!(err, Err(DecodeErr::MisMatchTarsTypeErr));
+
+        let mut de = TarsDecoder::from(&d[..]);
+        let err: Result<(i32,), DecodeErr> = DecodeTars::_decode(&mut de, 4);
+        assert_eq!(err, Err(DecodeErr::TarsTagNotFoundErr));
+    }
+
+    #[test]
+    fn test_decode_array() {
+        let d = b"\x09\x00\x02\x00\x01\x00\x02\x1d\x00\x00\x02\x01\x02\x2d\x00\x00\x02\x01\x02";
+        let mut de = TarsDecoder::from(&d[..]);
+        let a: [i32; 2] = DecodeTars::_decode(&mut de, 0).unwrap();
+        assert_eq!(a, [1, 2]);
+        let b: [u8; 2] = DecodeTars::_decode(&mut de, 1).unwrap();
+        assert_eq!(b, [1, 2]);
+        let c: [i8; 2] = DecodeTars::_decode(&mut de, 2).unwrap();
+        assert_eq!(c, [1, 2]);
+
+        let mut de = TarsDecoder::from(&d[..]);
+        let err: Result<[i32; 3], DecodeErr> = DecodeTars::_decode(&mut de, 0);
+        assert_eq!(err, Err(DecodeErr::MisMatchArrayLengthErr));
+        let err: Result<[u8; 1], DecodeErr> = DecodeTars::_decode(&mut de, 1);
+        assert_eq!(err, Err(DecodeErr::MisMatchArrayLengthErr));
+
+        // list 形式的 vector<unsigned char> 与 Vec<u8> 一样可以读出
+        let d = b"\x09\x00\x02\x00\x01\x01\x00\xff";
+        let mut de = TarsDecoder::from(&d[..]);
+        let b: [u8; 2] = DecodeTars::_decode(&mut de, 0).unwrap();
+        assert_eq!(b, [1, 255]);
+        let mut de = TarsDecoder::from(&d[..]);
+        let err: Result<[u8; 3], DecodeErr> = DecodeTars::_decode(&mut de, 0);
+        assert_eq!(err, Err(DecodeErr::MisMatchArrayLengthErr));
+    }
+
+    #[test]
+    fn test_decode_unsigned_out_of_range() {
+        // 70000, -1, 256, 4294967296, i64::max_value()
+        let d = b"\x02\x00\x01\x11\x70\x10\xff\x21\x01\x00\x33\x00\x00\x00\x01\x00\x00\x00\x00\x43\x7f\xff\xff\xff\xff\xff\xff\xff";
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_uint16(0, true, 0), Err(DecodeErr::ValueOutOfRange));
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_uint64(1, true, 0), Err(DecodeErr::ValueOutOfRange));
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_uint8(2, true, 0), Err(DecodeErr::ValueOutOfRange));
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_uint32(3, true, 0), Err(DecodeErr::ValueOutOfRange));
+
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_uint32(0, true, 0), Ok(70000));
+        assert_eq!(de.read_uint16(2, true, 0), Ok(256));
+        assert_eq!(de.read_uint64(3, true, 0), Ok(4294967296));
+        assert_eq!(de.read_uint64(4, true, 0), Ok(i64::max_value() as u64));
+        assert_eq!(de.read_uint64(5, false, u64::max_value()), Ok(u64::max_value()));
+        assert_eq!(de.read_uint64(5, true, 0), Err(DecodeErr::TarsTagNotFoundErr));
+    }
+
     #[test]
     fn test_decode_int64() {
         let b: [u8; 8] = unsafe { mem::transmute(0x0acb8b9d9d9d9d9di64.to_be()) };
@@ -1062,6 +1462,91 @@ mod tests {
     //     assert!(f == 0.35524f32);
     // }
 
+    #[test]
+    fn test_project() {
+        let mut map = BTreeMap::new();
+        map.insert("name".to_string(), (1i64, "foo".to_string()));
+        map.insert("uid".to_string(), (10086i64, "bar".to_string()));
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_string(0, "header").unwrap();
+        encoder.write_map(2, &map).unwrap();
+        encoder.write_list(3, &[(7i32,), (8i32,)]).unwrap();
+        encoder.write_int32(4, 9).unwrap();
+        let buf = encoder.to_bytes();
+
+        let uid = PathSegment::Key(TarsValue::String(Bytes::from("uid")));
+        let mut de = TarsDecoder::from(&buf);
+        assert_eq!(
+            de.project(&[PathSegment::Tag(2), uid.clone(), PathSegment::Tag(0)]),
+            Ok(TarsValue::Int(10086))
+        );
+        assert_eq!(
+            de.project(&[PathSegment::Tag(2), uid.clone(), PathSegment::Tag(1)]),
+            Ok(TarsValue::String(Bytes::from("bar")))
+        );
+        assert_eq!(
+            de.project(&[PathSegment::Tag(3), PathSegment::Index(1)]),
+            Ok(TarsValue::Struct(vec![(0, TarsValue::Int(8))]))
+        );
+        assert_eq!(
+            de.project_bytes(&[PathSegment::Tag(2), uid.clone()]),
+            Ok(Bytes::from(&b"\x1a\x01\x27\x66\x16\x03bar\x0b"[..]))
+        );
+
+        // 不存在的路径
+        assert_eq!(
+            de.project(&[PathSegment::Tag(2), uid.clone(), PathSegment::Tag(2)]),
+            Err(DecodeErr::TarsTagNotFoundErr)
+        );
+        assert_eq!(
+            de.project(&[PathSegment::Tag(3), PathSegment::Index(2)]),
+            Err(DecodeErr::TarsTagNotFoundErr)
+        );
+        assert_eq!(
+            de.project(&[PathSegment::Tag(4), PathSegment::Index(0)]),
+            Err(DecodeErr::MisMatchTarsTypeErr)
+        );
+
+        // 不影响后续读取
+        assert_eq!(de.read_string(0, true, String::new()), Ok("header".to_string()));
+        assert_eq!(de.read_int32(4, true, 0), Ok(9));
+    }
+
+    #[test]
+    fn test_decode_lenient_numeric() {
+        // 1.5f32, -3i8, 100000i32, 2.5f64
+        let d = b"\x04\x3f\xc0\x00\x00\x10\xfd\x22\x00\x01\x86\xa0\x35\x40\x04\x00\x00\x00\x00\x00\x00";
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_double(0, true, 0.0), Err(DecodeErr::MisMatchTarsTypeErr));
+        let mut de = TarsDecoder::from(&d[..]);
+        assert_eq!(de.read_float(1, true, 0.0), Err(DecodeErr::MisMatchTarsTypeErr));
+
+        let mut de = TarsDecoder::from(&d[..]).with_lenient_numeric(true);
+        assert_eq!(de.read_double(0, true, 0.0), Ok(1.5));
+        assert_eq!(de.read_float(1, true, 0.0), Ok(-3.0));
+        assert_eq!(de.read_double(2, true, 0.0), Ok(100000.0));
+        // 不做收窄
+        assert_eq!(de.read_float(3, true, 0.0), Err(DecodeErr::MisMatchTarsTypeErr));
+
+        // 16777217i32, 9007199254740993i64 超出 f32/f64 可精确表示的范围
+        let wide = b"\x02\x01\x00\x00\x01\x13\x00\x20\x00\x00\x00\x00\x00\x01";
+        let mut de = TarsDecoder::from(&wide[..]).with_lenient_numeric(true);
+        assert_eq!(
+            de.read_float(0, true, 0.0),
+            Err(DecodeErr::MisMatchTarsTypeErr)
+        );
+        let mut de = TarsDecoder::from(&wide[..]).with_lenient_numeric(true);
+        assert_eq!(de.read_double(0, true, 0.0), Ok(16777217.0));
+        assert_eq!(
+            de.read_double(1, true, 0.0),
+            Err(DecodeErr::MisMatchTarsTypeErr)
+        );
+
+        let mut de = TarsDecoder::from(&d[..]).with_lenient_numeric(true);
+        assert_eq!(de.read_int32(0, true, 0), Err(DecodeErr::MisMatchTarsTypeErr));
+    }
+
     #[test]
     fn test_decode_string() {
         // test read string1
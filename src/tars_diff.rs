@@ -0,0 +1,375 @@
+use bytes::Bytes;
+use errors::{DecodeErr, EncodeErr};
+use std::collections::BTreeMap;
+use std::fmt;
+use tars_encoder::TarsEncoder;
+use tars_value::{PathSegment, TarsValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TarsDiff {
+    Added {
+        path: Vec<PathSegment>,
+        value: TarsValue,
+    },
+    Removed {
+        path: Vec<PathSegment>,
+        value: TarsValue,
+    },
+    Changed {
+        path: Vec<PathSegment>,
+        old: TarsValue,
+        new: TarsValue,
+    },
+}
+
+impl TarsDiff {
+    pub fn path(&self) -> &[PathSegment] {
+        match *self {
+            TarsDiff::Added { ref path, .. } => path,
+            TarsDiff::Removed { ref path, .. } => path,
+            TarsDiff::Changed { ref path, .. } => path,
+        }
+    }
+}
+
+pub struct DisplayPath<'a>(pub &'a [PathSegment]);
+
+// 形如 2[3].1{"key"}.0
+impl<'a> fmt::Display for DisplayPath<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, seg) in self.0.iter().enumerate() {
+            match *seg {
+                PathSegment::Tag(tag) => if i == 0 {
+                    write!(f, "{}", tag)?
+                } else {
+                    write!(f, ".{}", tag)?
+                },
+                PathSegment::Index(idx) => write!(f, "[{}]", idx)?,
+                PathSegment::Key(ref key) => write!(f, "{{{}}}", key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TarsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TarsDiff::Added {
+                ref path,
+                ref value,
+            } => write!(f, "+ {}: {}", DisplayPath(path), value),
+            TarsDiff::Removed {
+                ref path,
+                ref value,
+            } => write!(f, "- {}: {}", DisplayPath(path), value),
+            TarsDiff::Changed {
+                ref path,
+                ref old,
+                ref new,
+            } => write!(f, "~ {}: {} -> {}", DisplayPath(path), old, new),
+        }
+    }
+}
+
+// 比较两个 buffer 的顶层 field
+// 整数宽度、String1/String4、EnZero 与 0.0、simplelist 与 list<byte> 等编码差异不计入
+pub fn diff(old: &Bytes, new: &Bytes) -> Result<Vec<TarsDiff>, DecodeErr> {
+    let old_fields = TarsValue::from_fields(old)?;
+    let new_fields = TarsValue::from_fields(new)?;
+    let mut diffs = vec![];
+    diff_fields(&mut vec![], &old_fields, &new_fields, &mut diffs)?;
+    Ok(diffs)
+}
+
+// map key 无法编码时返回错误
+pub fn diff_values(old: &TarsValue, new: &TarsValue) -> Result<Vec<TarsDiff>, EncodeErr> {
+    let mut diffs = vec![];
+    diff_value(&mut vec![], old, new, &mut diffs)?;
+    Ok(diffs)
+}
+
+// 每行一条差异，无差异时为空字符串
+pub fn diff_report(diffs: &[TarsDiff]) -> String {
+    let mut report = String::new();
+    for d in diffs {
+        report.push_str(&d.to_string());
+        report.push('\n');
+    }
+    report
+}
+
+fn diff_fields(
+    path: &mut Vec<PathSegment>,
+    old: &[(u8, TarsValue)],
+    new: &[(u8, TarsValue)],
+    diffs: &mut Vec<TarsDiff>,
+) -> Result<(), EncodeErr> {
+    // 重复 tag 以第一个为准，与 TarsDecoder 一致
+    let mut old_map: BTreeMap<u8, &TarsValue> = BTreeMap::new();
+    for &(tag, ref value) in old.iter() {
+        old_map.entry(tag).or_insert(value);
+    }
+    let mut new_map: BTreeMap<u8, &TarsValue> = BTreeMap::new();
+    for &(tag, ref value) in new.iter() {
+        new_map.entry(tag).or_insert(value);
+    }
+
+    for (&tag, old_value) in old_map.iter() {
+        path.push(PathSegment::Tag(tag));
+        match new_map.get(&tag) {
+            Some(new_value) => diff_value(path, old_value, new_value, diffs)?,
+            None => diffs.push(TarsDiff::Removed {
+                path: path.clone(),
+                value: (*old_value).clone(),
+            }),
+        }
+        path.pop();
+    }
+    for (&tag, new_value) in new_map.iter() {
+        if !old_map.contains_key(&tag) {
+            path.push(PathSegment::Tag(tag));
+            diffs.push(TarsDiff::Added {
+                path: path.clone(),
+                value: (*new_value).clone(),
+            });
+            path.pop();
+        }
+    }
+    Ok(())
+}
+
+fn diff_value(
+    path: &mut Vec<PathSegment>,
+    old: &TarsValue,
+    new: &TarsValue,
+    diffs: &mut Vec<TarsDiff>,
+) -> Result<(), EncodeErr> {
+    match (old, new) {
+        (TarsValue::Struct(o), TarsValue::Struct(n)) => diff_fields(path, o, n, diffs),
+        (TarsValue::List(o), TarsValue::List(n)) => diff_list(path, o, n, diffs),
+        (TarsValue::Map(o), TarsValue::Map(n)) => diff_map(path, o, n, diffs),
+        (TarsValue::SimpleList(o), TarsValue::List(n)) => {
+            diff_list(path, &bytes_to_list(o, n), n, diffs)
+        }
+        (TarsValue::List(o), TarsValue::SimpleList(n)) => {
+            diff_list(path, o, &bytes_to_list(n, o), diffs)
+        }
+        _ => {
+            if !same_scalar(old, new) {
+                diffs.push(TarsDiff::Changed {
+                    path: path.clone(),
+                    old: old.clone(),
+                    new: new.clone(),
+                })
+            }
+            Ok(())
+        }
+    }
+}
+
+fn diff_list(
+    path: &mut Vec<PathSegment>,
+    old: &[TarsValue],
+    new: &[TarsValue],
+    diffs: &mut Vec<TarsDiff>,
+) -> Result<(), EncodeErr> {
+    let len = old.len().max(new.len());
+    for i in 0..len {
+        path.push(PathSegment::Index(i));
+        match (old.get(i), new.get(i)) {
+            (Some(o), Some(n)) => diff_value(path, o, n, diffs)?,
+            (Some(o), None) => diffs.push(TarsDiff::Removed {
+                path: path.clone(),
+                value: o.clone(),
+            }),
+            (None, Some(n)) => diffs.push(TarsDiff::Added {
+                path: path.clone(),
+                value: n.clone(),
+            }),
+            (None, None) => {}
+        }
+        path.pop();
+    }
+    Ok(())
+}
+
+fn diff_map(
+    path: &mut Vec<PathSegment>,
+    old: &[(TarsValue, TarsValue)],
+    new: &[(TarsValue, TarsValue)],
+    diffs: &mut Vec<TarsDiff>,
+) -> Result<(), EncodeErr> {
+    let old_map = index_by_key(old)?;
+    let new_map = index_by_key(new)?;
+
+    for (key_bytes, &(key, old_value)) in old_map.iter() {
+        path.push(PathSegment::Key(key.clone()));
+        match new_map.get(key_bytes) {
+            Some(&(_, new_value)) => diff_value(path, old_value, new_value, diffs)?,
+            None => diffs.push(TarsDiff::Removed {
+                path: path.clone(),
+                value: old_value.clone(),
+            }),
+        }
+        path.pop();
+    }
+    for (key_bytes, &(key, new_value)) in new_map.iter() {
+        if !old_map.contains_key(key_bytes) {
+            path.push(PathSegment::Key(key.clone()));
+            diffs.push(TarsDiff::Added {
+                path: path.clone(),
+                value: new_value.clone(),
+            });
+            path.pop();
+        }
+    }
+    Ok(())
+}
+
+// 以 key 的规范编码作为索引，忽略 key 的编码差异
+fn index_by_key(
+    entries: &[(TarsValue, TarsValue)],
+) -> Result<BTreeMap<Bytes, (&TarsValue, &TarsValue)>, EncodeErr> {
+    let mut map = BTreeMap::new();
+    for (key, value) in entries.iter() {
+        let mut canonical = key.clone();
+        canonical.canonicalize()?;
+        let key_bytes = TarsEncoder::individual_encode(&canonical)?;
+        map.entry(key_bytes).or_insert((key, value));
+    }
+    Ok(map)
+}
+
+// simplelist 的字节可能以有符号或无符号写为 list 元素 (如 Vec<i8> 与 Vec<u8>)
+// 与对应元素按任一读法相等即视为相同
+fn bytes_to_list(b: &Bytes, other: &[TarsValue]) -> Vec<TarsValue> {
+    b.iter()
+        .enumerate()
+        .map(|(i, &x)| match other.get(i) {
+            Some(&TarsValue::Int(v)) if v == i64::from(x) => TarsValue::Int(v),
+            _ => TarsValue::Int(i64::from(x as i8)),
+        })
+        .collect()
+}
+
+// EnZero 统一读为 Int(0)，与 float/double 的 0 视为相同
+fn same_scalar(old: &TarsValue, new: &TarsValue) -> bool {
+    match (old, new) {
+        (TarsValue::Int(0), TarsValue::Float(f)) | (TarsValue::Float(f), TarsValue::Int(0)) => {
+            *f == 0.0
+        }
+        (TarsValue::Int(0), TarsValue::Double(f))
+        | (TarsValue::Double(f), TarsValue::Int(0)) => *f == 0.0,
+        _ => old == new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tars_encoder::*;
+
+    #[test]
+    fn test_diff_ignore_representation() {
+        let mut old = TarsEncoder::new().with_int_width(IntWidth::Fixed);
+        old.write_int32(0, 1).unwrap();
+        old.write_float(1, 0.0).unwrap();
+        old.write_list(2, &[1u8, 2, 200]).unwrap();
+        old.write_list(4, &[-1i8, 2]).unwrap();
+        old.write_string(3, "foo").unwrap();
+
+        let mut new = TarsEncoder::new();
+        new.write_int64(0, 1).unwrap();
+        new.write_float(1, 0.0).unwrap();
+        new.write_bytes(2, &[1u8, 2, 200]).unwrap();
+        new.write_string(3, "foo").unwrap();
+        new.write_bytes(4, &[0xff, 2]).unwrap();
+
+        assert_eq!(diff(&old.to_bytes(), &new.to_bytes()), Ok(vec![]));
+
+        // simplelist 与 list 的值不同时仍报告差异
+        let old = TarsValue::List(vec![TarsValue::Int(200), TarsValue::Int(1)]);
+        let new = TarsValue::SimpleList(Bytes::from(&[200u8, 2][..]));
+        assert_eq!(
+            diff_values(&old, &new),
+            Ok(vec![TarsDiff::Changed {
+                path: vec![PathSegment::Index(1)],
+                old: TarsValue::Int(1),
+                new: TarsValue::Int(2),
+            }])
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut old_map = BTreeMap::new();
+        old_map.insert("a".to_string(), 1i32);
+        old_map.insert("b".to_string(), 2i32);
+        let mut new_map = BTreeMap::new();
+        new_map.insert("b".to_string(), 3i32);
+        new_map.insert("c".to_string(), 4i32);
+
+        let mut old = TarsEncoder::new();
+        old.write_int32(0, 1).unwrap();
+        old.write_map(1, &old_map).unwrap();
+        old.write_list(2, &[(1i32,), (2i32,)]).unwrap();
+        old.write_string(3, "gone").unwrap();
+
+        let mut new = TarsEncoder::new();
+        new.write_int32(0, 1).unwrap();
+        new.write_map(1, &new_map).unwrap();
+        new.write_list(2, &[(1i32,), (5i32,), (6i32,)]).unwrap();
+        new.write_double(4, 0.5).unwrap();
+
+        let diffs = diff(&old.to_bytes(), &new.to_bytes()).unwrap();
+        let key = |s: &str| PathSegment::Key(TarsValue::String(Bytes::from(s)));
+        assert_eq!(
+            diffs,
+            vec![
+                TarsDiff::Removed {
+                    path: vec![PathSegment::Tag(1), key("a")],
+                    value: TarsValue::Int(1),
+                },
+                TarsDiff::Changed {
+                    path: vec![PathSegment::Tag(1), key("b")],
+                    old: TarsValue::Int(2),
+                    new: TarsValue::Int(3),
+                },
+                TarsDiff::Added {
+                    path: vec![PathSegment::Tag(1), key("c")],
+                    value: TarsValue::Int(4),
+                },
+                TarsDiff::Changed {
+                    path: vec![PathSegment::Tag(2), PathSegment::Index(1), PathSegment::Tag(0)],
+                    old: TarsValue::Int(2),
+                    new: TarsValue::Int(5),
+                },
+                TarsDiff::Added {
+                    path: vec![PathSegment::Tag(2), PathSegment::Index(2)],
+                    value: TarsValue::Struct(vec![(0, TarsValue::Int(6))]),
+                },
+                TarsDiff::Removed {
+                    path: vec![PathSegment::Tag(3)],
+                    value: TarsValue::String(Bytes::from("gone")),
+                },
+                TarsDiff::Added {
+                    path: vec![PathSegment::Tag(4)],
+                    value: TarsValue::Double(0.5),
+                },
+            ]
+        );
+
+        assert_eq!(
+            diff_report(&diffs),
+            "- 1{\"a\"}: 1\n\
+             ~ 1{\"b\"}: 2 -> 3\n\
+             + 1{\"c\"}: 4\n\
+             ~ 2[1].0: 2 -> 5\n\
+             + 2[2]: {0: 6}\n\
+             - 3: \"gone\"\n\
+             + 4: 0.5\n"
+        );
+    }
+}
@@ -0,0 +1,676 @@
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::rc::Rc;
+use std::sync::Arc;
+use tars_type::SimpleList;
+
+// 输出与 C++ TarsDisplayer 的 display()/displaySimple() 逐字节一致
+// char 与 unsigned char 按原始字节输出，bool 为 T/F，浮点数按 ostream 默认格式 (%g)
+#[derive(Debug, Clone, Default)]
+pub struct TarsDisplayer {
+    buf: Vec<u8>,
+    level: usize,
+}
+
+// Tars Struct 需要实现此 trait，按 tag 顺序逐个输出 field
+// _display_to 中调用 ds.display(&self.x, Some("x"))，
+// _display_simple_to 中调用 ds.display_simple(&self.x, true)，最后一个 field 的 sep 为 false
+pub trait StructDisplay {
+    fn _display_to(&self, ds: &mut TarsDisplayer);
+    fn _display_simple_to(&self, ds: &mut TarsDisplayer);
+}
+
+// 可被 TarsDisplayer 输出的类型需要实现此 trait
+// Tars Struct 调用 ds.display_struct / ds.display_simple_struct 即可
+// Tars Enum 与 C++ 一致，按 i32 输出
+pub trait TarsDisplay {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>);
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool);
+}
+
+impl TarsDisplayer {
+    pub fn new() -> Self {
+        TarsDisplayer::default()
+    }
+
+    pub fn with_level(mut self, level: usize) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn display<T>(&mut self, ele: &T, field_name: Option<&str>) -> &mut Self
+    where
+        T: TarsDisplay + ?Sized,
+    {
+        ele._display(self, field_name);
+        self
+    }
+
+    pub fn display_simple<T>(&mut self, ele: &T, sep: bool) -> &mut Self
+    where
+        T: TarsDisplay + ?Sized,
+    {
+        ele._display_simple(self, sep);
+        self
+    }
+
+    pub fn display_struct<T>(&mut self, ele: &T, field_name: Option<&str>) -> &mut Self
+    where
+        T: StructDisplay + ?Sized,
+    {
+        self.ps(field_name);
+        self.line(b"{");
+        self.nested(1, |ds| ele._display_to(ds));
+        self.ps(None);
+        self.line(b"}");
+        self
+    }
+
+    pub fn display_simple_struct<T>(&mut self, ele: &T, sep: bool) -> &mut Self
+    where
+        T: StructDisplay + ?Sized,
+    {
+        self.buf.push(b'{');
+        self.nested(1, |ds| ele._display_simple_to(ds));
+        self.buf.push(b'}');
+        self.sep(sep);
+        self
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    // 非 utf8 的字节 (如负数的 char) 会被替换
+    pub fn into_string(self) -> String {
+        String::from_utf8_lossy(&self.buf).into_owned()
+    }
+
+    fn ps(&mut self, field_name: Option<&str>) {
+        for _ in 0..self.level {
+            self.buf.push(b'\t');
+        }
+        if let Some(name) = field_name {
+            self.buf.extend_from_slice(name.as_bytes());
+            self.buf.extend_from_slice(b": ");
+        }
+    }
+
+    fn line(&mut self, s: &[u8]) {
+        self.buf.extend_from_slice(s);
+        self.buf.push(b'\n');
+    }
+
+    fn sep(&mut self, sep: bool) {
+        if sep {
+            self.buf.push(b'|');
+        }
+    }
+
+    fn nested<F>(&mut self, depth: usize, f: F)
+    where
+        F: FnOnce(&mut TarsDisplayer),
+    {
+        self.level += depth;
+        f(self);
+        self.level -= depth;
+    }
+
+    fn display_scalar(&mut self, s: &[u8], field_name: Option<&str>) {
+        self.ps(field_name);
+        self.line(s);
+    }
+
+    fn display_simple_scalar(&mut self, s: &[u8], sep: bool) {
+        self.buf.extend_from_slice(s);
+        self.sep(sep);
+    }
+
+    // vector<Char> 输出为长度与 hex
+    fn display_bin(&mut self, b: &[u8], field_name: Option<&str>) {
+        self.ps(field_name);
+        let s = format!("{}, {}", b.len(), bin2str(b));
+        self.line(s.as_bytes());
+    }
+
+    fn display_simple_bin(&mut self, b: &[u8], sep: bool) {
+        self.display_simple_scalar(bin2str(b).as_bytes(), sep);
+    }
+
+    fn display_list<'a, T, I>(&mut self, len: usize, iter: I, field_name: Option<&str>)
+    where
+        T: TarsDisplay + ?Sized + 'a,
+        I: Iterator<Item = &'a T>,
+    {
+        self.ps(field_name);
+        if len == 0 {
+            self.line(format!("{}, []", len).as_bytes());
+            return;
+        }
+        self.line(format!("{}, [", len).as_bytes());
+        self.nested(1, |ds| {
+            for ele in iter {
+                ds.display(ele, None);
+            }
+        });
+        self.ps(None);
+        self.line(b"]");
+    }
+
+    fn display_simple_list<'a, T, I>(&mut self, len: usize, iter: I, sep: bool)
+    where
+        T: TarsDisplay + ?Sized + 'a,
+        I: Iterator<Item = &'a T>,
+    {
+        self.buf.extend_from_slice(format!("{}{{", len).as_bytes());
+        self.nested(1, |ds| {
+            for (i, ele) in iter.enumerate() {
+                if i != 0 {
+                    ds.buf.push(b'|');
+                }
+                ds.display_simple(ele, false);
+            }
+        });
+        self.buf.push(b'}');
+        self.sep(sep);
+    }
+
+    fn display_map<'a, K, V, I>(&mut self, len: usize, iter: I, field_name: Option<&str>)
+    where
+        K: TarsDisplay + ?Sized + 'a,
+        V: TarsDisplay + ?Sized + 'a,
+        I: Iterator<Item = (&'a K, &'a V)>,
+    {
+        self.ps(field_name);
+        if len == 0 {
+            self.line(format!("{}, {{}}", len).as_bytes());
+            return;
+        }
+        self.line(format!("{}, {{", len).as_bytes());
+        for (k, v) in iter {
+            self.nested(1, |ds| ds.display_scalar(b"(", None));
+            self.nested(2, |ds| {
+                ds.display(k, None).display(v, None);
+            });
+            self.nested(1, |ds| ds.display_scalar(b")", None));
+        }
+        self.display_scalar(b"}", None);
+    }
+
+    fn display_simple_map<'a, K, V, I>(&mut self, len: usize, iter: I, sep: bool)
+    where
+        K: TarsDisplay + ?Sized + 'a,
+        V: TarsDisplay + ?Sized + 'a,
+        I: Iterator<Item = (&'a K, &'a V)>,
+    {
+        self.buf.extend_from_slice(format!("{}{{", len).as_bytes());
+        self.nested(2, |ds| {
+            for (i, (k, v)) in iter.enumerate() {
+                if i != 0 {
+                    ds.buf.push(b',');
+                }
+                ds.display_simple(k, true).display_simple(v, false);
+            }
+        });
+        self.buf.push(b'}');
+        self.sep(sep);
+    }
+}
+
+// 等同于 C++ struct 的 display(os)，field 从第 0 层开始，不输出外层括号
+pub fn display<T>(ele: &T) -> String
+where
+    T: StructDisplay + ?Sized,
+{
+    let mut ds = TarsDisplayer::new();
+    ele._display_to(&mut ds);
+    ds.into_string()
+}
+
+// 等同于 C++ struct 的 displaySimple(os)
+pub fn display_simple<T>(ele: &T) -> String
+where
+    T: StructDisplay + ?Sized,
+{
+    let mut ds = TarsDisplayer::new();
+    ele._display_simple_to(&mut ds);
+    ds.into_string()
+}
+
+// 与 TC_Common::bin2str 一致，小写 hex，无分隔符
+fn bin2str(b: &[u8]) -> String {
+    let mut s = String::with_capacity(b.len() * 2);
+    for byte in b {
+        s.push_str(&format!("{:02x}", byte));
+    }
+    s
+}
+
+// ostream 默认格式，等同于 printf("%g")，6 位有效数字
+fn format_g(f: f64) -> String {
+    if f.is_nan() {
+        return if f.is_sign_negative() { "-nan" } else { "nan" }.to_string();
+    }
+    if f.is_infinite() {
+        return if f < 0.0 { "-inf" } else { "inf" }.to_string();
+    }
+    if f == 0.0 {
+        return if f.is_sign_negative() { "-0" } else { "0" }.to_string();
+    }
+    const PRECISION: i32 = 6;
+    // 先按有效数字四舍五入，再由指数决定使用定点或科学计数法
+    let sci = format!("{:.*e}", (PRECISION - 1) as usize, f);
+    let (mantissa, exp) = sci.split_at(sci.find('e').unwrap_or(sci.len()));
+    let exp: i32 = exp[1..].parse().unwrap_or(0);
+    if !(-4..PRECISION).contains(&exp) {
+        format!(
+            "{}e{}{:02}",
+            trim_zeros(mantissa),
+            if exp < 0 { '-' } else { '+' },
+            exp.abs()
+        )
+    } else {
+        let fixed = format!("{:.*}", (PRECISION - 1 - exp) as usize, f);
+        trim_zeros(&fixed).to_string()
+    }
+}
+
+fn trim_zeros(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+macro_rules! impl_display_raw_char {
+    ($($t:ty),*) => {$(
+        impl TarsDisplay for $t {
+            fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+                ds.display_scalar(&[*self as u8], field_name);
+            }
+
+            fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+                ds.display_simple_scalar(&[*self as u8], sep);
+            }
+        }
+    )*}
+}
+
+impl_display_raw_char!(i8, u8);
+
+macro_rules! impl_display_int {
+    ($($t:ty),*) => {$(
+        impl TarsDisplay for $t {
+            fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+                ds.display_scalar(self.to_string().as_bytes(), field_name);
+            }
+
+            fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+                ds.display_simple_scalar(self.to_string().as_bytes(), sep);
+            }
+        }
+    )*}
+}
+
+impl_display_int!(i16, i32, i64, u16, u32, u64);
+
+impl TarsDisplay for f32 {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_scalar(format_g(f64::from(*self)).as_bytes(), field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_scalar(format_g(f64::from(*self)).as_bytes(), sep);
+    }
+}
+
+impl TarsDisplay for f64 {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_scalar(format_g(*self).as_bytes(), field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_scalar(format_g(*self).as_bytes(), sep);
+    }
+}
+
+impl TarsDisplay for bool {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_scalar(if *self { b"T" } else { b"F" }, field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_scalar(if *self { b"T" } else { b"F" }, sep);
+    }
+}
+
+impl TarsDisplay for str {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_scalar(self.as_bytes(), field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_scalar(self.as_bytes(), sep);
+    }
+}
+
+impl TarsDisplay for String {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        self.as_str()._display(ds, field_name)
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        self.as_str()._display_simple(ds, sep)
+    }
+}
+
+impl TarsDisplay for Bytes {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_bin(self, field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_bin(self, sep);
+    }
+}
+
+impl TarsDisplay for SimpleList {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_bin(self, field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_bin(self, sep);
+    }
+}
+
+impl<T> TarsDisplay for [T]
+where
+    T: TarsDisplay,
+{
+    default fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_list(self.len(), self.iter(), field_name);
+    }
+
+    default fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_list(self.len(), self.iter(), sep);
+    }
+}
+
+// vector<Char>
+impl TarsDisplay for [i8] {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        let b: Vec<u8> = self.iter().map(|&x| x as u8).collect();
+        ds.display_bin(&b, field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        let b: Vec<u8> = self.iter().map(|&x| x as u8).collect();
+        ds.display_simple_bin(&b, sep);
+    }
+}
+
+impl<T> TarsDisplay for Vec<T>
+where
+    T: TarsDisplay,
+{
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        self.as_slice()._display(ds, field_name)
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        self.as_slice()._display_simple(ds, sep)
+    }
+}
+
+impl<K, V> TarsDisplay for BTreeMap<K, V>
+where
+    K: TarsDisplay,
+    V: TarsDisplay,
+{
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        ds.display_map(self.len(), self.iter(), field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        ds.display_simple_map(self.len(), self.iter(), sep);
+    }
+}
+
+// C++ 的 map 有序，这里按 key 排序后输出
+impl<K, V, S> TarsDisplay for HashMap<K, V, S>
+where
+    K: TarsDisplay + Eq + Hash + Ord,
+    V: TarsDisplay,
+    S: BuildHasher,
+{
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        ds.display_map(self.len(), entries.into_iter(), field_name);
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        let mut entries: Vec<_> = self.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        ds.display_simple_map(self.len(), entries.into_iter(), sep);
+    }
+}
+
+// C++ 的 optional field 没有"不存在"的状态，未赋值时为默认值并照常输出
+// 因此 None 按 T::default() 输出；tars 文件中指定了非零默认值的 field 应以 Some 填入该值
+impl<T> TarsDisplay for Option<T>
+where
+    T: TarsDisplay + Default,
+{
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        match *self {
+            Some(ref v) => v._display(ds, field_name),
+            None => T::default()._display(ds, field_name),
+        }
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        match *self {
+            Some(ref v) => v._display_simple(ds, sep),
+            None => T::default()._display_simple(ds, sep),
+        }
+    }
+}
+
+macro_rules! impl_display_deref {
+    ($($t:ident),*) => {$(
+        impl<T> TarsDisplay for $t<T>
+        where
+            T: TarsDisplay + ?Sized,
+        {
+            fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+                (**self)._display(ds, field_name)
+            }
+
+            fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+                (**self)._display_simple(ds, sep)
+            }
+        }
+    )*}
+}
+
+impl_display_deref!(Box, Rc, Arc);
+
+impl<T> TarsDisplay for &T
+where
+    T: TarsDisplay + ?Sized,
+{
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        (**self)._display(ds, field_name)
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        (**self)._display_simple(ds, sep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sub {
+        x: i64,
+    }
+
+    impl StructDisplay for Sub {
+        fn _display_to(&self, ds: &mut TarsDisplayer) {
+            ds.display(&self.x, Some("x"));
+        }
+
+        fn _display_simple_to(&self, ds: &mut TarsDisplayer) {
+            ds.display_simple(&self.x, false);
+        }
+    }
+
+    impl TarsDisplay for Sub {
+        fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+            ds.display_struct(self, field_name);
+        }
+
+        fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+            ds.display_simple_struct(self, sep);
+        }
+    }
+
+    struct Foo {
+        a: i32,
+        c: i8,
+        s: String,
+        v: Vec<i32>,
+        m: BTreeMap<String, i32>,
+        f: f32,
+        b: bool,
+        e: Vec<Sub>,
+        bin: Bytes,
+        sub: Sub,
+    }
+
+    impl StructDisplay for Foo {
+        fn _display_to(&self, ds: &mut TarsDisplayer) {
+            ds.display(&self.a, Some("a"))
+                .display(&self.c, Some("c"))
+                .display(&self.s, Some("s"))
+                .display(&self.v, Some("v"))
+                .display(&self.m, Some("m"))
+                .display(&self.f, Some("f"))
+                .display(&self.b, Some("b"))
+                .display(&self.e, Some("e"))
+                .display(&self.bin, Some("bin"))
+                .display(&self.sub, Some("sub"));
+        }
+
+        fn _display_simple_to(&self, ds: &mut TarsDisplayer) {
+            ds.display_simple(&self.a, true)
+                .display_simple(&self.c, true)
+                .display_simple(&self.s, true)
+                .display_simple(&self.v, true)
+                .display_simple(&self.m, true)
+                .display_simple(&self.f, true)
+                .display_simple(&self.b, true)
+                .display_simple(&self.e, true)
+                .display_simple(&self.bin, true)
+                .display_simple(&self.sub, false);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let mut m = BTreeMap::new();
+        m.insert("k".to_string(), 3);
+        m.insert("j".to_string(), 4);
+        let foo = Foo {
+            a: 1,
+            c: b'x' as i8,
+            s: "hi".to_string(),
+            v: vec![1, 2],
+            m,
+            f: 1.5,
+            b: true,
+            e: vec![],
+            bin: Bytes::from(&b"\x01\xab"[..]),
+            sub: Sub { x: 7 },
+        };
+
+        assert_eq!(
+            display(&foo),
+            "a: 1\n\
+             c: x\n\
+             s: hi\n\
+             v: 2, [\n\
+             \t1\n\
+             \t2\n\
+             ]\n\
+             m: 2, {\n\
+             \t(\n\
+             \t\tj\n\
+             \t\t4\n\
+             \t)\n\
+             \t(\n\
+             \t\tk\n\
+             \t\t3\n\
+             \t)\n\
+             }\n\
+             f: 1.5\n\
+             b: T\n\
+             e: 0, []\n\
+             bin: 2, 01ab\n\
+             sub: {\n\
+             \tx: 7\n\
+             }\n"
+        );
+        assert_eq!(
+            display_simple(&foo),
+            "1|x|hi|2{1|2}|2{j|4,k|3}|1.5|T|0{}|01ab|{7}"
+        );
+
+        let mut ds = TarsDisplayer::new().with_level(1);
+        ds.display(&vec![Sub { x: 1 }], Some("subs"));
+        assert_eq!(
+            ds.into_string(),
+            "\tsubs: 1, [\n\t\t{\n\t\t\tx: 1\n\t\t}\n\t]\n"
+        );
+    }
+
+    #[test]
+    fn test_display_none() {
+        let mut ds = TarsDisplayer::new();
+        ds.display(&None::<i32>, Some("a"))
+            .display(&None::<String>, Some("s"))
+            .display(&Some(vec![1i32]), Some("v"))
+            .display(&None::<Vec<i32>>, Some("e"));
+        assert_eq!(ds.into_string(), "a: 0\ns: \nv: 1, [\n\t1\n]\ne: 0, []\n");
+
+        let mut ds = TarsDisplayer::new();
+        ds.display_simple(&None::<i32>, true)
+            .display_simple(&None::<String>, true)
+            .display_simple(&None::<Vec<i32>>, false);
+        assert_eq!(ds.into_string(), "0||0{}");
+    }
+
+    #[test]
+    fn test_format_g() {
+        assert_eq!(format_g(0.0), "0");
+        assert_eq!(format_g(100.0), "100");
+        assert_eq!(format_g(0.1), "0.1");
+        assert_eq!(format_g(f64::from(0.1f32)), "0.1");
+        assert_eq!(format_g(123456.0), "123456");
+        assert_eq!(format_g(1234567.0), "1.23457e+06");
+        assert_eq!(format_g(0.0001), "0.0001");
+        assert_eq!(format_g(0.00001234), "1.234e-05");
+        assert_eq!(format_g(-2.5), "-2.5");
+        assert_eq!(format_g(999999.5), "1e+06");
+        assert_eq!(format_g(1e100), "1e+100");
+    }
+}
@@ -1,30 +1,74 @@
 use bytes::{BufMut, Bytes, BytesMut};
 use errors::EncodeErr;
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hash};
 use std::mem;
+use std::rc::Rc;
+use std::sync::Arc;
 use tars_trait::{EnumToI32, StructToTars};
 use tars_type::TarsTypeMark::*;
 use tars_type::*;
+use tars_value::TarsValue;
 
 const MAX_HEADER_LEN: usize = 2;
 const MAX_SIZE_LEN: usize = 4;
 
+// 整数编码宽度
+// Compact: 按值压缩到最小宽度，0 写为 EnZero (默认)
+// Fixed: 按声明类型写入，i32 总是 EnInt32，0.0 也写为 EnFloat/EnDouble，不使用 EnZero，便于原地修改与逐字节比对
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum IntWidth {
+    #[default]
+    Compact,
+    Fixed,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TarsEncoder {
     buf: BytesMut,
+    sort_hash_map: bool,
+    int_width: IntWidth,
 }
 
 impl TarsEncoder {
     pub fn new() -> Self {
         TarsEncoder {
             buf: BytesMut::new(),
+            sort_hash_map: false,
+            int_width: IntWidth::Compact,
+        }
+    }
+
+    // HashMap 与 HashSet 按 key 排序写入，保证输出稳定，key 实现了 Ord 时与 BTreeMap 的输出一致
+    pub fn with_sorted_hash_map(mut self, sort_hash_map: bool) -> Self {
+        self.sort_hash_map = sort_hash_map;
+        self
+    }
+
+    // 影响整数 (含 bool, enum 与 list/map 长度) 与 float/double 的 0
+    pub fn with_int_width(mut self, int_width: IntWidth) -> Self {
+        self.int_width = int_width;
+        self
+    }
+
+    fn is_compact(&self) -> bool {
+        self.int_width == IntWidth::Compact
+    }
+
+    // 继承当前 encoder 的选项
+    fn sub_encoder(&self) -> Self {
+        TarsEncoder {
+            buf: BytesMut::new(),
+            sort_hash_map: self.sort_hash_map,
+            int_width: self.int_width,
         }
     }
 
     pub fn individual_encode<T>(ele: &T) -> Result<Bytes, EncodeErr>
     where
-        T: EncodeTars,
+        T: EncodeTars + ?Sized,
     {
         let mut encoder = TarsEncoder::new();
         ele._encode(&mut encoder, 0)?;
@@ -47,6 +91,33 @@ impl TarsEncoder {
         }
     }
 
+    // string 内容不要求是 utf8
+    fn put_string(&mut self, tag: u8, ele: &[u8]) -> Result<(), EncodeErr> {
+        let len = ele.len();
+        self.check_maybe_resize(MAX_SIZE_LEN + len);
+
+        if len <= usize::from(u8::max_value()) {
+            // encode as string1
+            self.put_head(tag, EnString1)?;
+            match u8::try_from(len) {
+                Ok(l) => {
+                    self.buf.put_u8(l);
+                    self.buf.put(ele);
+                    Ok(())
+                }
+                Err(_) => Err(EncodeErr::ConvertU8Err),
+            }
+        } else if len <= u32::max_value() as usize {
+            // encode as string4
+            self.put_head(tag, EnString4)?;
+            self.buf.put_u32_be(len as u32);
+            self.buf.put(ele);
+            Ok(())
+        } else {
+            Err(EncodeErr::DataTooBigErr)
+        }
+    }
+
     fn put_head(&mut self, tag: u8, tars_type: TarsTypeMark) -> Result<(), EncodeErr> {
         self.check_maybe_resize(MAX_HEADER_LEN);
         if tag > u8::max_value() {
@@ -77,19 +148,26 @@ pub trait TarsEncoderNormalTrait {
     fn write_uint8(&mut self, tag: u8, ele: u8) -> Result<(), EncodeErr>;
     fn write_uint16(&mut self, tag: u8, ele: u16) -> Result<(), EncodeErr>;
     fn write_uint32(&mut self, tag: u8, ele: u32) -> Result<(), EncodeErr>;
+    fn write_uint64(&mut self, tag: u8, ele: u64) -> Result<(), EncodeErr>;
 
     fn write_float(&mut self, tag: u8, ele: f32) -> Result<(), EncodeErr>;
     fn write_double(&mut self, tag: u8, ele: f64) -> Result<(), EncodeErr>;
 
-    fn write_string(&mut self, tag: u8, ele: &String) -> Result<(), EncodeErr>;
+    fn write_string(&mut self, tag: u8, ele: &str) -> Result<(), EncodeErr>;
 
-    fn write_bytes(&mut self, tag: u8, ele: &Bytes) -> Result<(), EncodeErr>;
+    fn write_bytes(&mut self, tag: u8, ele: &[u8]) -> Result<(), EncodeErr>;
 
     fn write_map<K, V>(&mut self, tag: u8, ele: &BTreeMap<K, V>) -> Result<(), EncodeErr>
     where
         K: EncodeTars + Ord,
         V: EncodeTars;
 
+    fn write_hash_map<K, V, S>(&mut self, tag: u8, ele: &HashMap<K, V, S>) -> Result<(), EncodeErr>
+    where
+        K: EncodeTars + Eq + Hash,
+        V: EncodeTars,
+        S: BuildHasher;
+
     fn write_enum<T>(&mut self, tag: u8, ele: &T) -> Result<(), EncodeErr>
     where
         T: EnumToI32;
@@ -103,12 +181,17 @@ pub trait TarsEncodeListTrait<T>
 where
     T: EncodeTars,
 {
-    fn write_list(&mut self, tag: u8, ele: &Vec<T>) -> Result<(), EncodeErr>;
+    fn write_list(&mut self, tag: u8, ele: &[T]) -> Result<(), EncodeErr>;
+
+    fn write_list_from_iter<'a, I>(&mut self, tag: u8, len: usize, ele: I) -> Result<(), EncodeErr>
+    where
+        I: Iterator<Item = &'a T>,
+        T: 'a;
 }
 
 impl TarsEncoderNormalTrait for TarsEncoder {
     fn write_int8(&mut self, tag: u8, ele: i8) -> Result<(), EncodeErr> {
-        if ele == 0 {
+        if ele == 0 && self.is_compact() {
             self.put_head(tag, EnZero)
         } else {
             self.put_head(tag, EnInt8)?;
@@ -123,7 +206,10 @@ impl TarsEncoderNormalTrait for TarsEncoder {
     }
 
     fn write_int16(&mut self, tag: u8, ele: i16) -> Result<(), EncodeErr> {
-        if ele >= i16::from(i8::min_value()) && ele <= i16::from(i8::max_value()) {
+        if self.is_compact()
+            && ele >= i16::from(i8::min_value())
+            && ele <= i16::from(i8::max_value())
+        {
             self.write_int8(tag, ele as i8)
         } else {
             self.put_head(tag, EnInt16)?;
@@ -134,7 +220,10 @@ impl TarsEncoderNormalTrait for TarsEncoder {
     }
 
     fn write_int32(&mut self, tag: u8, ele: i32) -> Result<(), EncodeErr> {
-        if ele >= i32::from(i16::min_value()) && ele <= i32::from(i16::max_value()) {
+        if self.is_compact()
+            && ele >= i32::from(i16::min_value())
+            && ele <= i32::from(i16::max_value())
+        {
             self.write_int16(tag, ele as i16)
         } else {
             self.put_head(tag, EnInt32)?;
@@ -145,7 +234,10 @@ impl TarsEncoderNormalTrait for TarsEncoder {
     }
 
     fn write_int64(&mut self, tag: u8, ele: i64) -> Result<(), EncodeErr> {
-        if ele >= i64::from(i32::min_value()) && ele <= i64::from(i32::max_value()) {
+        if self.is_compact()
+            && ele >= i64::from(i32::min_value())
+            && ele <= i64::from(i32::max_value())
+        {
             self.write_int32(tag, ele as i32)
         } else {
             self.put_head(tag, EnInt64)?;
@@ -167,8 +259,16 @@ impl TarsEncoderNormalTrait for TarsEncoder {
         self.write_int64(tag, ele as i64)
     }
 
+    // u64 以 int64 编码，超过 i64::max_value() 返回错误
+    fn write_uint64(&mut self, tag: u8, ele: u64) -> Result<(), EncodeErr> {
+        match i64::try_from(ele) {
+            Ok(i) => self.write_int64(tag, i),
+            Err(_) => Err(EncodeErr::ValueOutOfRange),
+        }
+    }
+
     fn write_float(&mut self, tag: u8, ele: f32) -> Result<(), EncodeErr> {
-        if ele == 0.0 {
+        if ele == 0.0 && self.is_compact() {
             self.put_head(tag, EnZero)?;
         } else {
             self.put_head(tag, EnFloat)?;
@@ -178,7 +278,7 @@ impl TarsEncoderNormalTrait for TarsEncoder {
         Ok(())
     }
     fn write_double(&mut self, tag: u8, ele: f64) -> Result<(), EncodeErr> {
-        if ele == 0.0 {
+        if ele == 0.0 && self.is_compact() {
             self.put_head(tag, EnZero)?;
         } else {
             self.put_head(tag, EnDouble)?;
@@ -187,33 +287,11 @@ impl TarsEncoderNormalTrait for TarsEncoder {
         }
         Ok(())
     }
-    fn write_string(&mut self, tag: u8, ele: &String) -> Result<(), EncodeErr> {
-        let len = ele.len();
-        self.check_maybe_resize(MAX_SIZE_LEN + len);
-
-        if len <= usize::from(u8::max_value()) {
-            // encode as string1
-            self.put_head(tag, EnString1)?;
-            match u8::try_from(len) {
-                Ok(l) => {
-                    self.buf.put_u8(l);
-                    self.buf.put(ele);
-                    Ok(())
-                }
-                Err(_) => Err(EncodeErr::ConvertU8Err),
-            }
-        } else if len <= u32::max_value() as usize {
-            // encode as string4
-            self.put_head(tag, EnString4)?;
-            self.buf.put_u32_be(len as u32);
-            self.buf.put(ele);
-            Ok(())
-        } else {
-            Err(EncodeErr::DataTooBigErr)
-        }
+    fn write_string(&mut self, tag: u8, ele: &str) -> Result<(), EncodeErr> {
+        self.put_string(tag, ele.as_bytes())
     }
 
-    fn write_bytes(&mut self, tag: u8, ele: &Bytes) -> Result<(), EncodeErr> {
+    fn write_bytes(&mut self, tag: u8, ele: &[u8]) -> Result<(), EncodeErr> {
         let len = ele.len();
         if len > i32::max_value() as usize {
             Err(EncodeErr::DataTooBigErr)
@@ -245,6 +323,35 @@ impl TarsEncoderNormalTrait for TarsEncoder {
         }
     }
 
+    fn write_hash_map<K, V, S>(&mut self, tag: u8, ele: &HashMap<K, V, S>) -> Result<(), EncodeErr>
+    where
+        K: EncodeTars + Eq + Hash,
+        V: EncodeTars,
+        S: BuildHasher,
+    {
+        let len = ele.len();
+        if len > i32::max_value() as usize {
+            Err(EncodeErr::DataTooBigErr)
+        } else {
+            self.put_head(tag, EnMaps)?;
+            self.write_int32(0, len as i32)?;
+            if self.sort_hash_map {
+                let mut entries: Vec<(&K, &V)> = ele.iter().collect();
+                K::sort_entries(&mut entries, self)?;
+                for (key, value) in entries {
+                    key._encode(self, 0)?;
+                    value._encode(self, 1)?;
+                }
+            } else {
+                for (key, value) in ele.iter() {
+                    key._encode(self, 0)?;
+                    value._encode(self, 1)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
     fn write_enum<T>(&mut self, tag: u8, ele: &T) -> Result<(), EncodeErr>
     where
         T: EnumToI32,
@@ -262,18 +369,79 @@ impl TarsEncoderNormalTrait for TarsEncoder {
     }
 }
 
+// sort_hash_map 开启时 HashMap/HashSet 的写入顺序
+// 实现了 Ord 的 key 按值排序，其余 key 按编码后的字节排序
+trait SortByKey: EncodeTars {
+    fn sort_entries<V>(
+        entries: &mut Vec<(&Self, V)>,
+        encoder: &TarsEncoder,
+    ) -> Result<(), EncodeErr>;
+}
+
+impl<T> SortByKey for T
+where
+    T: EncodeTars + ?Sized,
+{
+    default fn sort_entries<V>(
+        entries: &mut Vec<(&Self, V)>,
+        encoder: &TarsEncoder,
+    ) -> Result<(), EncodeErr> {
+        let mut keyed = Vec::with_capacity(entries.len());
+        for (key, value) in entries.drain(..) {
+            let mut key_encoder = encoder.sub_encoder();
+            key._encode(&mut key_encoder, 0)?;
+            keyed.push((key_encoder.buf, key, value));
+        }
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.extend(keyed.into_iter().map(|(_, key, value)| (key, value)));
+        Ok(())
+    }
+}
+
+impl<T> SortByKey for T
+where
+    T: EncodeTars + Ord + ?Sized,
+{
+    fn sort_entries<V>(entries: &mut Vec<(&Self, V)>, _: &TarsEncoder) -> Result<(), EncodeErr> {
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        Ok(())
+    }
+}
+
 impl<T> TarsEncodeListTrait<T> for TarsEncoder
 where
     T: EncodeTars,
 {
-    default fn write_list(&mut self, tag: u8, ele: &Vec<T>) -> Result<(), EncodeErr> {
+    default fn write_list(&mut self, tag: u8, ele: &[T]) -> Result<(), EncodeErr> {
         let len = ele.len();
         if len > i32::max_value() as usize {
             Err(EncodeErr::DataTooBigErr)
         } else {
             self.put_head(tag, EnList)?;
             self.write_int32(0, len as i32)?;
-            for ele in ele.into_iter() {
+            for ele in ele.iter() {
+                ele._encode(self, 0)?;
+            }
+            Ok(())
+        }
+    }
+
+    default fn write_list_from_iter<'a, I>(
+        &mut self,
+        tag: u8,
+        len: usize,
+        ele: I,
+    ) -> Result<(), EncodeErr>
+    where
+        I: Iterator<Item = &'a T>,
+        T: 'a,
+    {
+        if len > i32::max_value() as usize {
+            Err(EncodeErr::DataTooBigErr)
+        } else {
+            self.put_head(tag, EnList)?;
+            self.write_int32(0, len as i32)?;
+            for ele in ele {
                 ele._encode(self, 0)?;
             }
             Ok(())
@@ -282,7 +450,7 @@ where
 }
 
 impl TarsEncodeListTrait<i8> for TarsEncoder {
-    fn write_list(&mut self, tag: u8, ele: &Vec<i8>) -> Result<(), EncodeErr> {
+    fn write_list(&mut self, tag: u8, ele: &[i8]) -> Result<(), EncodeErr> {
         let len = ele.len();
         if len > i32::max_value() as usize {
             Err(EncodeErr::DataTooBigErr)
@@ -291,14 +459,32 @@ impl TarsEncodeListTrait<i8> for TarsEncoder {
             self.put_head(0, EnInt8)?;
             self.write_int32(0, len as i32)?;
             self.buf
-                .extend_from_slice(unsafe { mem::transmute(ele.as_slice()) });
+                .extend_from_slice(unsafe { mem::transmute(ele) });
+            Ok(())
+        }
+    }
+
+    fn write_list_from_iter<'a, I>(&mut self, tag: u8, len: usize, ele: I) -> Result<(), EncodeErr>
+    where
+        I: Iterator<Item = &'a i8>,
+    {
+        if len > i32::max_value() as usize {
+            Err(EncodeErr::DataTooBigErr)
+        } else {
+            self.put_head(tag, EnSimplelist)?;
+            self.put_head(0, EnInt8)?;
+            self.write_int32(0, len as i32)?;
+            self.check_maybe_resize(len);
+            for e in ele {
+                self.buf.put_u8(*e as u8);
+            }
             Ok(())
         }
     }
 }
 
 impl TarsEncodeListTrait<bool> for TarsEncoder {
-    fn write_list(&mut self, tag: u8, ele: &Vec<bool>) -> Result<(), EncodeErr> {
+    fn write_list(&mut self, tag: u8, ele: &[bool]) -> Result<(), EncodeErr> {
         let len = ele.len();
         if len > i32::max_value() as usize {
             Err(EncodeErr::DataTooBigErr)
@@ -307,7 +493,25 @@ impl TarsEncodeListTrait<bool> for TarsEncoder {
             self.put_head(0, EnInt8)?;
             self.write_int32(0, len as i32)?;
             self.buf
-                .extend_from_slice(unsafe { mem::transmute(ele.as_slice()) });
+                .extend_from_slice(unsafe { mem::transmute(ele) });
+            Ok(())
+        }
+    }
+
+    fn write_list_from_iter<'a, I>(&mut self, tag: u8, len: usize, ele: I) -> Result<(), EncodeErr>
+    where
+        I: Iterator<Item = &'a bool>,
+    {
+        if len > i32::max_value() as usize {
+            Err(EncodeErr::DataTooBigErr)
+        } else {
+            self.put_head(tag, EnSimplelist)?;
+            self.put_head(0, EnInt8)?;
+            self.write_int32(0, len as i32)?;
+            self.check_maybe_resize(len);
+            for e in ele {
+                self.buf.put_u8(*e as u8);
+            }
             Ok(())
         }
     }
@@ -360,6 +564,12 @@ impl EncodeTars for u32 {
     }
 }
 
+impl EncodeTars for u64 {
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_uint64(tag, *self)
+    }
+}
+
 impl EncodeTars for f32 {
     fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
         encoder.write_float(tag, *self)
@@ -384,6 +594,12 @@ impl EncodeTars for String {
     }
 }
 
+impl EncodeTars for str {
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_string(tag, self)
+    }
+}
+
 impl<K, V> EncodeTars for BTreeMap<K, V>
 where
     K: EncodeTars + Ord,
@@ -403,12 +619,204 @@ where
     }
 }
 
+impl<T> EncodeTars for [T]
+where
+    T: EncodeTars,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_list(tag, self)
+    }
+}
+
+impl<K, V, S> EncodeTars for HashMap<K, V, S>
+where
+    K: EncodeTars + Eq + Hash,
+    V: EncodeTars,
+    S: BuildHasher,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_hash_map(tag, self)
+    }
+}
+
+impl<T> EncodeTars for VecDeque<T>
+where
+    T: EncodeTars,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_list_from_iter(tag, self.len(), self.iter())
+    }
+}
+
+impl<T> EncodeTars for BTreeSet<T>
+where
+    T: EncodeTars + Ord,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_list_from_iter(tag, self.len(), self.iter())
+    }
+}
+
+impl<T, S> EncodeTars for HashSet<T, S>
+where
+    T: EncodeTars + Eq + Hash,
+    S: BuildHasher,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        if encoder.sort_hash_map {
+            let mut entries: Vec<(&T, ())> = self.iter().map(|ele| (ele, ())).collect();
+            T::sort_entries(&mut entries, encoder)?;
+            encoder.write_list_from_iter(tag, self.len(), entries.into_iter().map(|e| e.0))
+        } else {
+            encoder.write_list_from_iter(tag, self.len(), self.iter())
+        }
+    }
+}
+
 impl EncodeTars for Bytes {
     fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
         encoder.write_bytes(tag, self)
     }
 }
 
+impl EncodeTars for TarsValue {
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        match *self {
+            TarsValue::Int(i) => encoder.write_int64(tag, i),
+            TarsValue::Float(f) => encoder.write_float(tag, f),
+            TarsValue::Double(f) => encoder.write_double(tag, f),
+            TarsValue::String(ref s) => encoder.put_string(tag, s),
+            TarsValue::SimpleList(ref b) => encoder.write_bytes(tag, b),
+            TarsValue::List(ref v) => encoder.write_list(tag, v),
+            TarsValue::Map(ref entries) => {
+                let len = entries.len();
+                if len > i32::max_value() as usize {
+                    Err(EncodeErr::DataTooBigErr)
+                } else {
+                    encoder.put_head(tag, EnMaps)?;
+                    encoder.write_int32(0, len as i32)?;
+                    for (key, value) in entries.iter() {
+                        key._encode(encoder, 0)?;
+                        value._encode(encoder, 1)?;
+                    }
+                    Ok(())
+                }
+            }
+            TarsValue::Struct(ref fields) => {
+                encoder.put_head(tag, EnStructBegin)?;
+                for &(t, ref value) in fields.iter() {
+                    value._encode(encoder, t)?;
+                }
+                encoder.put_head(0, EnStructEnd)
+            }
+        }
+    }
+}
+
+impl EncodeTars for SimpleList {
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_bytes(tag, self)
+    }
+}
+
+impl<T, const N: usize> EncodeTars for [T; N]
+where
+    T: EncodeTars,
+{
+    default fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_list(tag, &self[..])
+    }
+}
+
+// [u8; N] 与 Bytes 一样以 simplelist 编码
+impl<const N: usize> EncodeTars for [u8; N] {
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_bytes(tag, &self[..])
+    }
+}
+
+// tuple 以匿名 struct 编码，元素依次写入 tag 0..n
+macro_rules! impl_tuple_encode {
+    ($($name:ident: $tag:tt),+) => {
+        impl<$($name),+> StructToTars for ($($name,)+)
+        where
+            $($name: EncodeTars),+
+        {
+            fn _encode_to(&self, encoder: &mut TarsEncoder) -> Result<(), EncodeErr> {
+                $(self.$tag._encode(encoder, $tag)?;)+
+                Ok(())
+            }
+        }
+
+        impl<$($name),+> EncodeTars for ($($name,)+)
+        where
+            $($name: EncodeTars),+
+        {
+            fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+                encoder.write_struct(tag, self)
+            }
+        }
+    };
+}
+
+impl_tuple_encode!(A: 0);
+impl_tuple_encode!(A: 0, B: 1);
+impl_tuple_encode!(A: 0, B: 1, C: 2);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+impl_tuple_encode!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
+
+impl<T> EncodeTars for &T
+where
+    T: EncodeTars + ?Sized,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        (**self)._encode(encoder, tag)
+    }
+}
+
+impl<T> EncodeTars for Box<T>
+where
+    T: EncodeTars + ?Sized,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        (**self)._encode(encoder, tag)
+    }
+}
+
+impl<T> EncodeTars for Rc<T>
+where
+    T: EncodeTars + ?Sized,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        (**self)._encode(encoder, tag)
+    }
+}
+
+impl<T> EncodeTars for Arc<T>
+where
+    T: EncodeTars + ?Sized,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        (**self)._encode(encoder, tag)
+    }
+}
+
+impl<'a, T> EncodeTars for Cow<'a, T>
+where
+    T: EncodeTars + ToOwned + ?Sized,
+{
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        (**self)._encode(encoder, tag)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -538,6 +946,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_fixed_int_width() {
+        let mut encoder = TarsEncoder::new().with_int_width(IntWidth::Fixed);
+        encoder.write_int8(0, 0).unwrap();
+        encoder.write_int16(1, 1).unwrap();
+        encoder.write_int32(2, 0).unwrap();
+        encoder.write_int64(3, -1).unwrap();
+        encoder.write_boolean(4, false).unwrap();
+        encoder.write_uint8(5, 1).unwrap();
+        encoder.write_float(6, 0.0).unwrap();
+        encoder.write_double(7, 0.0).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x00\x00\x11\x00\x01\x22\x00\x00\x00\x00\x33\xff\xff\xff\xff\xff\xff\xff\xff\x40\x00\x51\x00\x01\x64\x00\x00\x00\x00\x75\x00\x00\x00\x00\x00\x00\x00\x00"[..]
+        );
+
+        // list 长度与元素同样按声明宽度写入，子 encoder 继承选项
+        let mut encoder = TarsEncoder::new().with_int_width(IntWidth::Fixed);
+        encoder.write_list(0, &[7i32]).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x09\x02\x00\x00\x00\x01\x02\x00\x00\x00\x07"[..]
+        );
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int32(0, 0).unwrap();
+        encoder.write_int32(1, 1).unwrap();
+        assert_eq!(&encoder.to_bytes(), &b"\x0c\x10\x01"[..]);
+    }
+
+    #[test]
+    fn test_encode_u64() {
+        let mut encoder = TarsEncoder::new();
+        encoder.write_uint64(0, 88888).unwrap();
+        encoder
+            .write_uint64(1, i64::max_value() as u64)
+            .unwrap();
+        assert_eq!(
+            encoder.write_uint64(2, i64::max_value() as u64 + 1),
+            Err(EncodeErr::ValueOutOfRange)
+        );
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x02\x00\x01\x5b\x38\x13\x7f\xff\xff\xff\xff\xff\xff\xff"[..]
+        );
+    }
+
     #[test]
     fn test_encode_f32() {
         let mut encoder = TarsEncoder::new();
@@ -642,6 +1097,161 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_encode_hash_map() {
+        let mut map: HashMap<String, i32> = HashMap::new();
+        map.insert("world".to_string(), 42);
+        map.insert("hello".to_string(), 32);
+
+        let mut encoder = TarsEncoder::new().with_sorted_hash_map(true);
+        encoder.write_hash_map(0, &map).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x08\x00\x02\x06\x05hello\x10\x20\x06\x05world\x10\x2a"[..]
+        );
+
+        let mut map2: HashMap<i64, Vec<String>> = HashMap::with_capacity(1024);
+        let mut map3: HashMap<i64, Vec<String>> = HashMap::new();
+        for i in 0..100 {
+            map2.insert(i * 1000, vec![i.to_string()]);
+        }
+        for i in (0..100).rev() {
+            map3.insert(i * 1000, vec![i.to_string()]);
+        }
+        let mut encoder2 = TarsEncoder::new().with_sorted_hash_map(true);
+        let mut encoder3 = TarsEncoder::new().with_sorted_hash_map(true);
+        encoder2.write_hash_map(0, &map2).unwrap();
+        encoder3.write_hash_map(0, &map3).unwrap();
+        assert_eq!(encoder2.to_bytes(), encoder3.to_bytes());
+
+        // 按 key 的值排序，与 BTreeMap 的输出一致
+        let ints: HashMap<i32, i32> = [(1, 0), (-1, 0), (300, 0)].iter().cloned().collect();
+        let strings: HashMap<String, i32> =
+            ["b", "aa"].iter().map(|s| (s.to_string(), 0)).collect();
+        let mut encoder = TarsEncoder::new().with_sorted_hash_map(true);
+        encoder.write_hash_map(0, &ints).unwrap();
+        encoder.write_hash_map(1, &strings).unwrap();
+        let mut expect = TarsEncoder::new();
+        expect
+            .write_map(0, &ints.into_iter().collect::<BTreeMap<_, _>>())
+            .unwrap();
+        expect
+            .write_map(1, &strings.into_iter().collect::<BTreeMap<_, _>>())
+            .unwrap();
+        assert_eq!(encoder.to_bytes(), expect.to_bytes());
+    }
+
+    #[test]
+    fn test_encode_set_and_deque() {
+        let mut set: BTreeSet<i32> = BTreeSet::new();
+        set.insert(2);
+        set.insert(1);
+        let mut encoder = TarsEncoder::new();
+        set._encode(&mut encoder, 0).unwrap();
+        assert_eq!(&encoder.to_bytes(), &b"\x09\x00\x02\x00\x01\x00\x02"[..]);
+
+        let mut deque: VecDeque<i8> = VecDeque::new();
+        deque.push_back(2);
+        deque.push_front(1);
+        let mut encoder = TarsEncoder::new();
+        deque._encode(&mut encoder, 0).unwrap();
+        assert_eq!(&encoder.to_bytes(), &b"\x0d\x00\x00\x02\x01\x02"[..]);
+
+        let mut hash_set: HashSet<bool> = HashSet::new();
+        hash_set.insert(true);
+        let mut encoder = TarsEncoder::new();
+        hash_set._encode(&mut encoder, 3).unwrap();
+        assert_eq!(&encoder.to_bytes(), &b"\x3d\x00\x00\x01\x01"[..]);
+
+        let hash_set: HashSet<i32> = (-100..100).collect();
+        let mut encoder = TarsEncoder::new().with_sorted_hash_map(true);
+        hash_set._encode(&mut encoder, 0).unwrap();
+        let mut expect = TarsEncoder::new();
+        let set: BTreeSet<i32> = hash_set.into_iter().collect();
+        set._encode(&mut expect, 0).unwrap();
+        assert_eq!(encoder.to_bytes(), expect.to_bytes());
+    }
+
+    #[test]
+    fn test_encode_borrowed() {
+        let s = String::from("hello wrold!");
+        let v: Vec<i32> = vec![1, 2, 3];
+
+        let mut expect = TarsEncoder::new();
+        s._encode(&mut expect, 0).unwrap();
+        v._encode(&mut expect, 1).unwrap();
+        expect.write_bytes(2, &Bytes::from(&b"foo"[..])).unwrap();
+        let expect = expect.to_bytes();
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_string(0, "hello wrold!").unwrap();
+        encoder.write_list(1, &v[..]).unwrap();
+        encoder.write_bytes(2, b"foo").unwrap();
+        assert_eq!(encoder.to_bytes(), expect);
+
+        let mut encoder = TarsEncoder::new();
+        Cow::Borrowed(s.as_str())._encode(&mut encoder, 0).unwrap();
+        Arc::new(v.clone())._encode(&mut encoder, 1).unwrap();
+        Rc::new(Bytes::from(&b"foo"[..]))._encode(&mut encoder, 2).unwrap();
+        assert_eq!(encoder.to_bytes(), expect);
+
+        let mut encoder = TarsEncoder::new();
+        (&&s)._encode(&mut encoder, 0).unwrap();
+        let boxed: Box<[i32]> = v.clone().into_boxed_slice();
+        boxed._encode(&mut encoder, 1).unwrap();
+        let owned: Cow<Bytes> = Cow::Owned(Bytes::from(&b"foo"[..]));
+        owned._encode(&mut encoder, 2).unwrap();
+        assert_eq!(encoder.to_bytes(), expect);
+
+        assert_eq!(
+            TarsEncoder::individual_encode("hello wrold!").unwrap(),
+            TarsEncoder::individual_encode(&s).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encode_tuple() {
+        let mut encoder = TarsEncoder::new();
+        (1i32, "foo".to_string(), 0u8)._encode(&mut encoder, 2).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x2a\x00\x01\x16\x03foo\x2c\x0b"[..]
+        );
+
+        let mut encoder = TarsEncoder::new();
+        let t = (0i8, 1i8, 2i8, 3i8, 4i8, 5i8, 6i8, 7i8, 8i8, 9i8, 10i8, 11i8);
+        t._encode(&mut encoder, 0).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x0a\x0c\x10\x01\x20\x02\x30\x03\x40\x04\x50\x05\x60\x06\x70\x07\x80\x08\x90\x09\xa0\x0a\xb0\x0b\x0b"[..]
+        );
+    }
+
+    #[test]
+    fn test_encode_array() {
+        let mut encoder = TarsEncoder::new();
+        [1i32, 2]._encode(&mut encoder, 0).unwrap();
+        [1u8, 2]._encode(&mut encoder, 1).unwrap();
+        [1i8, 2]._encode(&mut encoder, 2).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x09\x00\x02\x00\x01\x00\x02\x1d\x00\x00\x02\x01\x02\x2d\x00\x00\x02\x01\x02"[..]
+        );
+    }
+
+    #[test]
+    fn test_encode_simple_list() {
+        let v: Vec<u8> = vec![1, 255];
+        let mut encoder = TarsEncoder::new();
+        encoder.write_list(0, &v).unwrap();
+        encoder.write_bytes(1, &v).unwrap();
+        SimpleList::from(v)._encode(&mut encoder, 2).unwrap();
+        assert_eq!(
+            &encoder.to_bytes(),
+            &b"\x09\x00\x02\x00\x01\x01\x00\xff\x1d\x00\x00\x02\x01\xff\x2d\x00\x00\x02\x01\xff"[..]
+        );
+    }
+
     #[test]
     fn test_encode_bytes() {
         let b = Bytes::from(&b"hello world!"[..]);
@@ -0,0 +1,668 @@
+use base64;
+use bytes::Bytes;
+use errors::{DecodeErr, JsonErr};
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeMap, HashMap};
+use tars_class_name::split_class_name;
+use tars_decoder::{DecodeTars, TarsDecoder};
+use tars_encoder::{EncodeTars, TarsEncoder};
+use tars_type::SimpleList;
+use tars_value::TarsValue;
+use tup_uni_attribute::TupUniAttribute;
+
+// 对应 tars 文件中的类型，用于按字段名转换 json
+#[derive(Debug, Clone, PartialEq)]
+pub enum TarsSchema {
+    Bool,
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+    Bytes,
+    List(Box<TarsSchema>),
+    Map(Box<TarsSchema>, Box<TarsSchema>),
+    Struct(Vec<SchemaField>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaField {
+    pub tag: u8,
+    pub name: String,
+    pub schema: TarsSchema,
+}
+
+impl SchemaField {
+    pub fn new(tag: u8, name: &str, schema: TarsSchema) -> Self {
+        SchemaField {
+            tag,
+            name: name.to_string(),
+            schema,
+        }
+    }
+}
+
+// 生成代码中的类型实现此 trait 即可使用 to_json/from_json
+pub trait TarsSchemaOf {
+    fn _schema() -> TarsSchema;
+}
+
+macro_rules! impl_schema_of {
+    ($($t:ty => $schema:ident),*) => {
+        $(
+            impl TarsSchemaOf for $t {
+                fn _schema() -> TarsSchema {
+                    TarsSchema::$schema
+                }
+            }
+        )*
+    };
+}
+
+impl_schema_of!(
+    bool => Bool,
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    u8 => Short,
+    u16 => Int,
+    u32 => Long,
+    u64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    Bytes => Bytes,
+    SimpleList => Bytes
+);
+
+impl<T: TarsSchemaOf> TarsSchemaOf for Vec<T> {
+    fn _schema() -> TarsSchema {
+        TarsSchema::List(Box::new(T::_schema()))
+    }
+}
+
+impl<K: TarsSchemaOf, V: TarsSchemaOf> TarsSchemaOf for BTreeMap<K, V> {
+    fn _schema() -> TarsSchema {
+        TarsSchema::Map(Box::new(K::_schema()), Box::new(V::_schema()))
+    }
+}
+
+impl<K: TarsSchemaOf, V: TarsSchemaOf, S> TarsSchemaOf for HashMap<K, V, S> {
+    fn _schema() -> TarsSchema {
+        TarsSchema::Map(Box::new(K::_schema()), Box::new(V::_schema()))
+    }
+}
+
+// optional 字段不存在时 json 中省略
+impl<T: TarsSchemaOf> TarsSchemaOf for Option<T> {
+    fn _schema() -> TarsSchema {
+        T::_schema()
+    }
+}
+
+pub fn to_json<T>(ele: &T) -> Result<Value, JsonErr>
+where
+    T: EncodeTars + TarsSchemaOf,
+{
+    let buf = TarsEncoder::individual_encode(ele)?;
+    let (_, value) = TarsValue::from_field(&buf)?;
+    value_to_json(&value, Some(&T::_schema()))
+}
+
+pub fn from_json<T>(json: &Value) -> Result<T, JsonErr>
+where
+    T: DecodeTars + TarsSchemaOf,
+{
+    let value = json_to_value(json, Some(&T::_schema()))?;
+    let buf = TarsEncoder::individual_encode(&value)?;
+    Ok(TarsDecoder::individual_decode(&buf)?)
+}
+
+// buffer 中的顶层 field 转为 json object
+// 给出 schema 时以字段名为 key，否则以 tag 为 key；schema 中没有的 tag 同样以 tag 为 key
+pub fn fields_to_json(buf: &[u8], schema: Option<&[SchemaField]>) -> Result<Value, JsonErr> {
+    let fields = TarsValue::from_fields(buf)?;
+    struct_to_json(&fields, schema)
+}
+
+pub fn json_to_fields(json: &Value, schema: Option<&[SchemaField]>) -> Result<Bytes, JsonErr> {
+    let fields = json_to_struct(json, schema)?;
+    Ok(TarsValue::encode_fields(&fields)?)
+}
+
+// 无 schema 时：整数为 number，float/double 为 number (读回时为 double)，
+// simplelist 为 {"$bytes": base64}，map 为 {"$map": [[key, value], ...]}，struct 为以 tag 为 key 的 object
+pub fn value_to_json(value: &TarsValue, schema: Option<&TarsSchema>) -> Result<Value, JsonErr> {
+    match schema {
+        None => schemaless_to_json(value),
+        Some(schema) => schema_to_json(value, schema),
+    }
+}
+
+pub fn json_to_value(json: &Value, schema: Option<&TarsSchema>) -> Result<TarsValue, JsonErr> {
+    match schema {
+        None => schemaless_from_json(json),
+        Some(schema) => schema_from_json(json, schema),
+    }
+}
+
+// 由线上的类型名得到 schema，各语言的写法均可；struct 等无法识别的类型为 None
+pub fn schema_from_class_name(class_name: &str) -> Option<TarsSchema> {
+    let (name, args) = split_class_name(class_name)?;
+    let schema = match (name.as_str(), args.len()) {
+        ("bool", 0) => TarsSchema::Bool,
+        ("char", 0) => TarsSchema::Byte,
+        ("short", 0) => TarsSchema::Short,
+        ("int32", 0) => TarsSchema::Int,
+        ("int64", 0) => TarsSchema::Long,
+        ("float", 0) => TarsSchema::Float,
+        ("double", 0) => TarsSchema::Double,
+        ("string", 0) => TarsSchema::String,
+        ("list", 1) if args[0] == "char" => TarsSchema::Bytes,
+        ("list", 1) => TarsSchema::List(Box::new(schema_from_class_name(&args[0])?)),
+        ("map", 2) => TarsSchema::Map(
+            Box::new(schema_from_class_name(&args[0])?),
+            Box::new(schema_from_class_name(&args[1])?),
+        ),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+// 用于调试输出，不需要注册 rust 类型
+// TupSimple 为 {key: value}，value 按无 schema 转换
+// TupComplex 为 {key: {className: value}}，能识别类型名时按其转换，否则 (或与数据不符时) 按无 schema 转换
+pub fn tup_to_json(uni: &TupUniAttribute) -> Result<Value, JsonErr> {
+    let mut obj = Map::new();
+    for entry in uni.iter() {
+        let (_, value) = TarsValue::from_field(entry.value)?;
+        match entry.class_name {
+            None => {
+                obj.insert(entry.name.to_string(), schemaless_to_json(&value)?);
+            }
+            Some(class_name) => {
+                let json = match schema_from_class_name(class_name) {
+                    Some(schema) => {
+                        schema_to_json(&value, &schema).or_else(|_| schemaless_to_json(&value))?
+                    }
+                    None => schemaless_to_json(&value)?,
+                };
+                if let Value::Object(ref mut item) = *obj
+                    .entry(entry.name.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()))
+                {
+                    item.insert(class_name.to_string(), json);
+                }
+            }
+        }
+    }
+    Ok(Value::Object(obj))
+}
+
+fn mismatch(json: &Value, expected: &str) -> JsonErr {
+    JsonErr::InvalidJsonErr(format!("expect {}, found {}", expected, json))
+}
+
+fn out_of_range(json: &Value, min: i64, max: i64) -> JsonErr {
+    JsonErr::OutOfRangeErr(format!("{} not in [{}, {}]", json, min, max))
+}
+
+// NaN 与 inf 无法用 json number 表示，以字符串 "NaN"、"Infinity"、"-Infinity" 表示
+fn float_to_json(f: f64) -> Value {
+    match Number::from_f64(f) {
+        Some(n) => Value::Number(n),
+        None if f.is_nan() => Value::String("NaN".to_string()),
+        None if f > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+// 无 schema 时字符串会读为 string，NaN 与 inf 写为 {"$double": "NaN"}
+fn schemaless_float_to_json(f: f64) -> Value {
+    match float_to_json(f) {
+        Value::String(s) => {
+            let mut obj = Map::new();
+            obj.insert("$double".to_string(), Value::String(s));
+            Value::Object(obj)
+        }
+        other => other,
+    }
+}
+
+fn bytes_to_json(b: &[u8]) -> Value {
+    Value::String(base64::encode(b))
+}
+
+fn bytes_from_json(json: &Value) -> Result<Bytes, JsonErr> {
+    match *json {
+        Value::String(ref s) => base64::decode(s)
+            .map(Bytes::from)
+            .map_err(|e| JsonErr::InvalidJsonErr(format!("invalid base64, {}", e))),
+        _ => Err(mismatch(json, "base64 string")),
+    }
+}
+
+// vector<char> 可能以 simplelist 编码，统一按 list 处理
+fn simple_list_to_list(b: &[u8]) -> Vec<TarsValue> {
+    b.iter().map(|&x| TarsValue::Int(i64::from(x as i8))).collect()
+}
+
+fn schemaless_to_json(value: &TarsValue) -> Result<Value, JsonErr> {
+    match *value {
+        TarsValue::Int(i) => Ok(Value::from(i)),
+        TarsValue::Float(f) => Ok(schemaless_float_to_json(f64::from(f))),
+        TarsValue::Double(f) => Ok(schemaless_float_to_json(f)),
+        TarsValue::String(ref s) => Ok(Value::String(String::from_utf8_lossy(s).into_owned())),
+        TarsValue::SimpleList(ref b) => {
+            let mut obj = Map::new();
+            obj.insert("$bytes".to_string(), bytes_to_json(b));
+            Ok(Value::Object(obj))
+        }
+        TarsValue::List(ref v) => v.iter()
+            .map(schemaless_to_json)
+            .collect::<Result<Vec<Value>, JsonErr>>()
+            .map(Value::Array),
+        TarsValue::Map(ref entries) => {
+            let mut pairs = vec![];
+            for (key, value) in entries.iter() {
+                pairs.push(Value::Array(vec![
+                    schemaless_to_json(key)?,
+                    schemaless_to_json(value)?,
+                ]));
+            }
+            let mut obj = Map::new();
+            obj.insert("$map".to_string(), Value::Array(pairs));
+            Ok(Value::Object(obj))
+        }
+        TarsValue::Struct(ref fields) => struct_to_json(fields, None),
+    }
+}
+
+fn schemaless_from_json(json: &Value) -> Result<TarsValue, JsonErr> {
+    match *json {
+        Value::Bool(b) => Ok(TarsValue::Int(b as i64)),
+        Value::Number(ref n) => if let Some(i) = n.as_i64() {
+            Ok(TarsValue::Int(i))
+        } else if n.is_u64() {
+            Err(out_of_range(json, i64::MIN, i64::MAX))
+        } else {
+            Ok(TarsValue::Double(n.as_f64().unwrap_or_default()))
+        },
+        Value::String(ref s) => Ok(TarsValue::String(Bytes::from(s.as_bytes()))),
+        Value::Array(ref v) => v.iter()
+            .map(schemaless_from_json)
+            .collect::<Result<Vec<TarsValue>, JsonErr>>()
+            .map(TarsValue::List),
+        Value::Object(ref obj) => {
+            if let Some(b) = obj.get("$bytes") {
+                return bytes_from_json(b).map(TarsValue::SimpleList);
+            }
+            if let Some(f) = obj.get("$double") {
+                return float_from_json(f).map(TarsValue::Double);
+            }
+            if let Some(pairs) = obj.get("$map") {
+                let pairs = match *pairs {
+                    Value::Array(ref pairs) => pairs,
+                    _ => return Err(mismatch(pairs, "array of [key, value]")),
+                };
+                let mut entries = vec![];
+                for pair in pairs.iter() {
+                    match *pair {
+                        Value::Array(ref kv) if kv.len() == 2 => entries.push((
+                            schemaless_from_json(&kv[0])?,
+                            schemaless_from_json(&kv[1])?,
+                        )),
+                        _ => return Err(mismatch(pair, "[key, value]")),
+                    }
+                }
+                return Ok(TarsValue::Map(entries));
+            }
+            json_to_struct(json, None).map(TarsValue::Struct)
+        }
+        Value::Null => Err(mismatch(json, "value")),
+    }
+}
+
+fn schema_to_json(value: &TarsValue, schema: &TarsSchema) -> Result<Value, JsonErr> {
+    match (schema, value) {
+        (TarsSchema::Bool, TarsValue::Int(i)) => Ok(Value::Bool(*i != 0)),
+        (TarsSchema::Byte, TarsValue::Int(i))
+        | (TarsSchema::Short, TarsValue::Int(i))
+        | (TarsSchema::Int, TarsValue::Int(i))
+        | (TarsSchema::Long, TarsValue::Int(i)) => Ok(Value::from(*i)),
+        (TarsSchema::Float, TarsValue::Int(i)) | (TarsSchema::Double, TarsValue::Int(i)) => {
+            Ok(float_to_json(*i as f64))
+        }
+        (TarsSchema::Float, TarsValue::Float(f)) | (TarsSchema::Double, TarsValue::Float(f)) => {
+            Ok(float_to_json(f64::from(*f)))
+        }
+        (TarsSchema::Float, TarsValue::Double(f)) | (TarsSchema::Double, TarsValue::Double(f)) => {
+            Ok(float_to_json(*f))
+        }
+        (TarsSchema::String, TarsValue::String(s)) => {
+            Ok(Value::String(String::from_utf8_lossy(s).into_owned()))
+        }
+        (TarsSchema::Bytes, TarsValue::SimpleList(b)) => Ok(bytes_to_json(b)),
+        (TarsSchema::Bytes, TarsValue::List(v)) => {
+            let mut b = Vec::with_capacity(v.len());
+            for ele in v.iter() {
+                match *ele {
+                    TarsValue::Int(i) => b.push(i as u8),
+                    _ => return Err(JsonErr::Decode(DecodeErr::MisMatchTarsTypeErr)),
+                }
+            }
+            Ok(bytes_to_json(&b))
+        }
+        (TarsSchema::List(e), TarsValue::List(v)) => v.iter()
+            .map(|ele| schema_to_json(ele, e))
+            .collect::<Result<Vec<Value>, JsonErr>>()
+            .map(Value::Array),
+        (TarsSchema::List(e), TarsValue::SimpleList(b)) => {
+            schema_to_json(&TarsValue::List(simple_list_to_list(b)), &TarsSchema::List(e.clone()))
+        }
+        (TarsSchema::Map(k, v), TarsValue::Map(entries)) => {
+            let mut obj = Map::new();
+            for (key, value) in entries.iter() {
+                let key = match schema_to_json(key, k)? {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                obj.insert(key, schema_to_json(value, v)?);
+            }
+            Ok(Value::Object(obj))
+        }
+        (TarsSchema::Struct(fields), TarsValue::Struct(v)) => struct_to_json(v, Some(fields)),
+        _ => Err(JsonErr::Decode(DecodeErr::MisMatchTarsTypeErr)),
+    }
+}
+
+fn int_from_json(json: &Value, min: i64, max: i64) -> Result<TarsValue, JsonErr> {
+    match json.as_i64() {
+        Some(i) if i >= min && i <= max => Ok(TarsValue::Int(i)),
+        Some(_) => Err(out_of_range(json, min, max)),
+        None if json.is_u64() => Err(out_of_range(json, min, max)),
+        None => Err(mismatch(json, "integer")),
+    }
+}
+
+fn float_from_json(json: &Value) -> Result<f64, JsonErr> {
+    match *json {
+        Value::String(ref s) if s == "NaN" => Ok(f64::NAN),
+        Value::String(ref s) if s == "Infinity" => Ok(f64::INFINITY),
+        Value::String(ref s) if s == "-Infinity" => Ok(f64::NEG_INFINITY),
+        _ => json.as_f64().ok_or_else(|| mismatch(json, "number")),
+    }
+}
+
+fn schema_from_json(json: &Value, schema: &TarsSchema) -> Result<TarsValue, JsonErr> {
+    match *schema {
+        TarsSchema::Bool => match *json {
+            Value::Bool(b) => Ok(TarsValue::Int(b as i64)),
+            _ => Err(mismatch(json, "bool")),
+        },
+        TarsSchema::Byte => int_from_json(json, i64::from(i8::MIN), i64::from(i8::MAX)),
+        TarsSchema::Short => int_from_json(json, i64::from(i16::MIN), i64::from(i16::MAX)),
+        TarsSchema::Int => int_from_json(json, i64::from(i32::MIN), i64::from(i32::MAX)),
+        TarsSchema::Long => int_from_json(json, i64::MIN, i64::MAX),
+        TarsSchema::Float => float_from_json(json).map(|f| TarsValue::Float(f as f32)),
+        TarsSchema::Double => float_from_json(json).map(TarsValue::Double),
+        TarsSchema::String => match *json {
+            Value::String(ref s) => Ok(TarsValue::String(Bytes::from(s.as_bytes()))),
+            _ => Err(mismatch(json, "string")),
+        },
+        TarsSchema::Bytes => bytes_from_json(json).map(TarsValue::SimpleList),
+        TarsSchema::List(ref e) => match *json {
+            Value::Array(ref v) => {
+                let list = v.iter()
+                    .map(|ele| schema_from_json(ele, e))
+                    .collect::<Result<Vec<TarsValue>, JsonErr>>()?;
+                match **e {
+                    // 与 Vec<i8>/Vec<bool> 的编码保持一致，写为 simplelist
+                    TarsSchema::Byte | TarsSchema::Bool => Ok(TarsValue::SimpleList(
+                        list.iter()
+                            .map(|ele| match *ele {
+                                TarsValue::Int(i) => i as u8,
+                                _ => unreachable!("byte and bool are always read as Int"),
+                            })
+                            .collect::<Vec<u8>>()
+                            .into(),
+                    )),
+                    _ => Ok(TarsValue::List(list)),
+                }
+            }
+            _ => Err(mismatch(json, "array")),
+        },
+        TarsSchema::Map(ref k, ref v) => match *json {
+            Value::Object(ref obj) => {
+                let mut entries = vec![];
+                for (key, value) in obj.iter() {
+                    // 非 string 类型的 key 以 json 文本保存
+                    let key = match **k {
+                        TarsSchema::String | TarsSchema::Bytes => Value::String(key.clone()),
+                        _ => ::serde_json::from_str(key).map_err(|_| mismatch(&Value::String(key.clone()), "map key"))?,
+                    };
+                    entries.push((schema_from_json(&key, k)?, schema_from_json(value, v)?));
+                }
+                Ok(TarsValue::Map(entries))
+            }
+            _ => Err(mismatch(json, "object")),
+        },
+        TarsSchema::Struct(ref fields) => json_to_struct(json, Some(fields)).map(TarsValue::Struct),
+    }
+}
+
+fn struct_to_json(
+    fields: &[(u8, TarsValue)],
+    schema: Option<&[SchemaField]>,
+) -> Result<Value, JsonErr> {
+    let mut obj = Map::new();
+    for (tag, value) in fields.iter() {
+        let field = schema.and_then(|s| s.iter().find(|f| f.tag == *tag));
+        match field {
+            Some(f) => obj.insert(f.name.clone(), schema_to_json(value, &f.schema)?),
+            None => obj.insert(tag.to_string(), schemaless_to_json(value)?),
+        };
+    }
+    Ok(Value::Object(obj))
+}
+
+fn json_to_struct(
+    json: &Value,
+    schema: Option<&[SchemaField]>,
+) -> Result<Vec<(u8, TarsValue)>, JsonErr> {
+    let obj = match *json {
+        Value::Object(ref obj) => obj,
+        _ => return Err(mismatch(json, "object")),
+    };
+    let mut fields = vec![];
+    for (key, value) in obj.iter() {
+        // null 表示 optional 字段不存在
+        if value.is_null() {
+            continue;
+        }
+        let field = schema.and_then(|s| s.iter().find(|f| f.name == *key));
+        match field {
+            Some(f) => fields.push((f.tag, schema_from_json(value, &f.schema)?)),
+            None => match key.parse::<u8>() {
+                Ok(tag) => fields.push((tag, schemaless_from_json(value)?)),
+                Err(_) => return Err(JsonErr::UnknownFieldErr(key.clone())),
+            },
+        }
+    }
+    fields.sort_by_key(|f| f.0);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tars_encoder::*;
+    use tars_type::ProtocolVersion;
+    use tup_uni_packet::RequestPacket;
+
+    #[test]
+    fn test_schemaless_json() {
+        let mut map = BTreeMap::new();
+        map.insert(1i32, "a".to_string());
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int64(0, i64::MAX).unwrap();
+        encoder.write_string(1, "foo").unwrap();
+        encoder.write_bytes(2, b"\x00\xff").unwrap();
+        encoder.write_map(3, &map).unwrap();
+        encoder.write_struct(4, &(0.5f64, vec![1i32, 2])).unwrap();
+        let buf = encoder.to_bytes();
+
+        let json = fields_to_json(&buf, None).unwrap();
+        assert_eq!(
+            json.to_string(),
+            r#"{"0":9223372036854775807,"1":"foo","2":{"$bytes":"AP8="},"3":{"$map":[[1,"a"]]},"4":{"0":0.5,"1":[1,2]}}"#
+        );
+        assert_eq!(json_to_fields(&json, None), Ok(buf));
+    }
+
+    #[test]
+    fn test_schema_json() {
+        let schema = vec![
+            SchemaField::new(0, "id", TarsSchema::Long),
+            SchemaField::new(1, "name", TarsSchema::String),
+            SchemaField::new(2, "data", TarsSchema::Bytes),
+            SchemaField::new(3, "scores", <BTreeMap<i32, f32>>::_schema()),
+            SchemaField::new(4, "flags", <Vec<bool>>::_schema()),
+        ];
+
+        let mut scores = BTreeMap::new();
+        scores.insert(-1i32, 1.5f32);
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int64(0, -9007199254740993).unwrap();
+        encoder.write_string(1, "foo").unwrap();
+        encoder.write_bytes(2, b"bar").unwrap();
+        encoder.write_map(3, &scores).unwrap();
+        encoder.write_list(4, &[true, false]).unwrap();
+        encoder.write_int32(9, 7).unwrap();
+        let buf = encoder.to_bytes();
+
+        let json = fields_to_json(&buf, Some(&schema)).unwrap();
+        assert_eq!(
+            json.to_string(),
+            r#"{"9":7,"data":"YmFy","flags":[true,false],"id":-9007199254740993,"name":"foo","scores":{"-1":1.5}}"#
+        );
+        assert_eq!(json_to_fields(&json, Some(&schema)), Ok(buf));
+
+        let bad: Value = ::serde_json::from_str(r#"{"idd": 1}"#).unwrap();
+        assert_eq!(
+            json_to_fields(&bad, Some(&schema)),
+            Err(JsonErr::UnknownFieldErr("idd".to_string()))
+        );
+        let bad: Value = ::serde_json::from_str(r#"{"flags": [1]}"#).unwrap();
+        assert!(json_to_fields(&bad, Some(&schema)).is_err());
+    }
+
+    #[test]
+    fn test_non_finite_json() {
+        let schema = vec![
+            SchemaField::new(0, "nan", TarsSchema::Double),
+            SchemaField::new(1, "inf", TarsSchema::Float),
+            SchemaField::new(2, "ninf", TarsSchema::Double),
+        ];
+        let mut encoder = TarsEncoder::new();
+        encoder.write_double(0, f64::NAN).unwrap();
+        encoder.write_float(1, f32::INFINITY).unwrap();
+        encoder.write_double(2, f64::NEG_INFINITY).unwrap();
+        let buf = encoder.to_bytes();
+
+        let json = fields_to_json(&buf, Some(&schema)).unwrap();
+        assert_eq!(
+            json.to_string(),
+            r#"{"inf":"Infinity","nan":"NaN","ninf":"-Infinity"}"#
+        );
+        assert_eq!(json_to_fields(&json, Some(&schema)), Ok(buf.clone()));
+
+        let json = fields_to_json(&buf, None).unwrap();
+        assert_eq!(
+            json.to_string(),
+            r#"{"0":{"$double":"NaN"},"1":{"$double":"Infinity"},"2":{"$double":"-Infinity"}}"#
+        );
+        let mut encoder = TarsEncoder::new();
+        encoder.write_double(0, f64::NAN).unwrap();
+        encoder.write_double(1, f64::INFINITY).unwrap();
+        encoder.write_double(2, f64::NEG_INFINITY).unwrap();
+        assert_eq!(json_to_fields(&json, None), Ok(encoder.to_bytes()));
+
+        // null 不再表示 NaN
+        let json: Value = ::serde_json::from_str("null").unwrap();
+        assert_eq!(
+            from_json::<f64>(&json),
+            Err(JsonErr::InvalidJsonErr("expect number, found null".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_typed_json() {
+        let mut v: BTreeMap<String, Vec<i8>> = BTreeMap::new();
+        v.insert("k".to_string(), vec![-1, 2]);
+        let json = to_json(&v).unwrap();
+        assert_eq!(json.to_string(), r#"{"k":[-1,2]}"#);
+        assert_eq!(from_json::<BTreeMap<String, Vec<i8>>>(&json), Ok(v));
+
+        let json: Value = ::serde_json::from_str("300").unwrap();
+        assert_eq!(
+            from_json::<i8>(&json),
+            Err(JsonErr::OutOfRangeErr("300 not in [-128, 127]".to_string()))
+        );
+        let json: Value = ::serde_json::from_str("18446744073709551615").unwrap();
+        assert_eq!(
+            from_json::<i64>(&json),
+            Err(JsonErr::OutOfRangeErr(
+                "18446744073709551615 not in [-9223372036854775808, 9223372036854775807]"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tup_to_json() {
+        assert_eq!(
+            schema_from_class_name("map<string,List<int32>>"),
+            Some(TarsSchema::Map(
+                Box::new(TarsSchema::String),
+                Box::new(TarsSchema::List(Box::new(TarsSchema::Int)))
+            ))
+        );
+        assert_eq!(schema_from_class_name("vector<char>"), Some(TarsSchema::Bytes));
+        assert_eq!(schema_from_class_name("list<Test.Foo>"), None);
+
+        let mut map = BTreeMap::new();
+        map.insert(1i32, "a".to_string());
+
+        let packet = RequestPacket {
+            func_name: "hello".to_string(),
+            ..Default::default()
+        };
+
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write("b", &true).unwrap();
+        uni.write("b", &"x".to_string()).unwrap();
+        uni.write("bytes", &Bytes::from(&b"\x00\xff"[..])).unwrap();
+        uni.write("m", &map).unwrap();
+        uni.write("p", &packet).unwrap();
+        let json = tup_to_json(&uni).unwrap();
+        assert_eq!(json["b"].to_string(), r#"{"bool":true,"string":"x"}"#);
+        assert_eq!(json["bytes"].to_string(), r#"{"list<byte>":"AP8="}"#);
+        assert_eq!(json["m"].to_string(), r#"{"map<int32,string>":{"1":"a"}}"#);
+        // struct 没有 schema，按 tag 输出
+        assert_eq!(json["p"]["tars.RequestPacket"]["6"], "hello");
+
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupSimple);
+        uni.write("b", &true).unwrap();
+        uni.write("m", &map).unwrap();
+        assert_eq!(
+            tup_to_json(&uni).unwrap().to_string(),
+            r#"{"b":1,"m":{"$map":[[1,"a"]]}}"#
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use bytes::Bytes;
+use errors::{DecodeErr, EncodeErr};
+use tars_encoder::{EncodeTars, TarsEncoder};
+use tars_reader::{TarsEvent, TarsReader};
+use tars_type::TarsTypeMark;
+use tars_type::TarsTypeMark::*;
+
+// 路径为逐层的 struct tag，最后一个为被修改的 tag
+#[derive(Debug, Clone, PartialEq)]
+enum PatchOp {
+    Set(Vec<u8>, Bytes),
+    Remove(Vec<u8>),
+    Append(Vec<u8>, Bytes),
+}
+
+// 修改已编码的 buffer，未修改的 field (包括不认识的 field) 按原字节复制
+// 操作按添加顺序依次执行，效果与逐个修改相同
+#[derive(Debug, Clone)]
+pub struct TarsPatcher {
+    buf: Bytes,
+    ops: Vec<PatchOp>,
+}
+
+#[derive(Debug)]
+struct Span {
+    tag: u8,
+    tars_type: TarsTypeMark,
+    start: usize,
+    end: usize,
+}
+
+impl TarsPatcher {
+    pub fn new(buf: Bytes) -> Self {
+        TarsPatcher { buf, ops: vec![] }
+    }
+
+    // tag 已存在时替换第一个同 tag 的 field，否则按 tag 顺序插入
+    pub fn set<T>(&mut self, tag: u8, value: &T) -> Result<&mut Self, EncodeErr>
+    where
+        T: EncodeTars + ?Sized,
+    {
+        self.set_path(&[tag], value)
+    }
+
+    pub fn set_path<T>(&mut self, path: &[u8], value: &T) -> Result<&mut Self, EncodeErr>
+    where
+        T: EncodeTars + ?Sized,
+    {
+        let field = encode_field(path, value)?;
+        self.ops.push(PatchOp::Set(path.to_vec(), field));
+        Ok(self)
+    }
+
+    // 删除所有同 tag 的 field，不存在时忽略
+    pub fn remove(&mut self, tag: u8) -> &mut Self {
+        self.remove_path(&[tag])
+    }
+
+    pub fn remove_path(&mut self, path: &[u8]) -> &mut Self {
+        self.ops.push(PatchOp::Remove(path.to_vec()));
+        self
+    }
+
+    // 追加到所在层的末尾，不检查 tag 是否已存在
+    pub fn append<T>(&mut self, tag: u8, value: &T) -> Result<&mut Self, EncodeErr>
+    where
+        T: EncodeTars + ?Sized,
+    {
+        self.append_path(&[tag], value)
+    }
+
+    pub fn append_path<T>(&mut self, path: &[u8], value: &T) -> Result<&mut Self, EncodeErr>
+    where
+        T: EncodeTars + ?Sized,
+    {
+        let field = encode_field(path, value)?;
+        self.ops.push(PatchOp::Append(path.to_vec(), field));
+        Ok(self)
+    }
+
+    // 每一层只解析一次，该层的操作依次作用于 field 列表后统一输出
+    pub fn patch(&self) -> Result<Bytes, DecodeErr> {
+        let ops: Vec<(&[u8], &PatchOp)> = self.ops.iter().map(|op| (op.path(), op)).collect();
+        let mut reader = TarsReader::new(&self.buf);
+        let mut out = Vec::with_capacity(self.buf.len());
+        patch_level(&self.buf, &mut reader, &ops, &mut out)?;
+        Ok(Bytes::from(out))
+    }
+}
+
+impl PatchOp {
+    fn path(&self) -> &[u8] {
+        match *self {
+            PatchOp::Set(ref path, _) | PatchOp::Remove(ref path) | PatchOp::Append(ref path, _) => {
+                path
+            }
+        }
+    }
+}
+
+// 执行操作过程中同一层的 field
+enum Item<'a> {
+    // 原有或新写入的 field，按原字节输出
+    Field(u8, TarsTypeMark, &'a [u8]),
+    // 内部有修改的 struct field 及作用于其内部的操作
+    Nested(u8, &'a [u8], Vec<(&'a [u8], &'a PatchOp)>),
+}
+
+impl<'a> Item<'a> {
+    fn tag(&self) -> u8 {
+        match *self {
+            Item::Field(tag, _, _) | Item::Nested(tag, _, _) => tag,
+        }
+    }
+}
+
+fn encode_field<T>(path: &[u8], value: &T) -> Result<Bytes, EncodeErr>
+where
+    T: EncodeTars + ?Sized,
+{
+    let mut encoder = TarsEncoder::new();
+    value._encode(&mut encoder, *path.last().unwrap_or(&0))?;
+    Ok(encoder.to_bytes())
+}
+
+// 读取同一层的 field 直到 buffer 末尾或当前 struct 结束，返回各 field 的范围与该层的结束位置
+fn split_level(reader: &mut TarsReader) -> Result<(Vec<Span>, usize), DecodeErr> {
+    let depth = reader.depth();
+    let mut spans = vec![];
+    loop {
+        let start = TarsReader::position(reader);
+        match reader.next() {
+            None => return Ok((spans, start)),
+            Some(Err(e)) => return Err(e),
+            Some(Ok(TarsEvent::StructEnd)) => return Ok((spans, start)),
+            Some(Ok(TarsEvent::Field { tag, tars_type })) => {
+                skip_value(reader, depth)?;
+                spans.push(Span {
+                    tag,
+                    tars_type,
+                    start,
+                    end: TarsReader::position(reader),
+                });
+            }
+            Some(Ok(_)) => return Err(DecodeErr::MisMatchTarsTypeErr),
+        }
+    }
+}
+
+fn skip_value(reader: &mut TarsReader, depth: usize) -> Result<(), DecodeErr> {
+    loop {
+        match reader.next() {
+            Some(Ok(_)) => if reader.depth() == depth {
+                return Ok(());
+            },
+            Some(Err(e)) => return Err(e),
+            None => return Err(DecodeErr::NoEnoughDataErr),
+        }
+    }
+}
+
+// 读取 reader 所在的一层并执行 ops，该层之前与之后的字节 (struct 的 head 与 EnStructEnd) 原样复制
+fn patch_level<'a>(
+    buf: &'a [u8],
+    reader: &mut TarsReader<'a>,
+    ops: &[(&'a [u8], &'a PatchOp)],
+    out: &mut Vec<u8>,
+) -> Result<(), DecodeErr> {
+    let begin = TarsReader::position(reader);
+    let (spans, end) = split_level(reader)?;
+    let mut items: Vec<Item<'a>> = spans
+        .into_iter()
+        .map(|s| Item::Field(s.tag, s.tars_type, &buf[s.start..s.end]))
+        .collect();
+    for &(path, op) in ops.iter() {
+        apply_op(&mut items, path, op)?;
+    }
+
+    out.extend_from_slice(&buf[..begin]);
+    for item in items.iter() {
+        match *item {
+            Item::Field(_, _, field) => out.extend_from_slice(field),
+            Item::Nested(_, field, ref nested) => patch_struct(field, nested, out)?,
+        }
+    }
+    out.extend_from_slice(&buf[end..]);
+    Ok(())
+}
+
+fn apply_op<'a>(items: &mut Vec<Item<'a>>, path: &'a [u8], op: &'a PatchOp) -> Result<(), DecodeErr> {
+    let tag = match path.first() {
+        Some(&tag) => tag,
+        None => return Err(DecodeErr::TarsTagNotFoundErr),
+    };
+
+    if path.len() > 1 {
+        let item = match items.iter_mut().find(|i| i.tag() == tag) {
+            Some(item) => item,
+            None => return Err(DecodeErr::TarsTagNotFoundErr),
+        };
+        let field = match *item {
+            Item::Field(_, EnStructBegin, field) => field,
+            Item::Field(..) => return Err(DecodeErr::MisMatchTarsTypeErr),
+            Item::Nested(_, _, ref mut nested) => {
+                nested.push((&path[1..], op));
+                return Ok(());
+            }
+        };
+        *item = Item::Nested(tag, field, vec![(&path[1..], op)]);
+        return Ok(());
+    }
+
+    match *op {
+        PatchOp::Set(_, ref field) => {
+            let new = Item::Field(tag, field_type(field), field);
+            match items.iter().position(|i| i.tag() == tag) {
+                Some(idx) => items[idx] = new,
+                None => {
+                    let idx = items
+                        .iter()
+                        .position(|i| i.tag() > tag)
+                        .unwrap_or(items.len());
+                    items.insert(idx, new);
+                }
+            }
+        }
+        PatchOp::Remove(_) => items.retain(|i| i.tag() != tag),
+        PatchOp::Append(_, ref field) => items.push(Item::Field(tag, field_type(field), field)),
+    }
+    Ok(())
+}
+
+// encode_field 的结果至少包含 head
+fn field_type(field: &[u8]) -> TarsTypeMark {
+    TarsTypeMark::from(field[0] & 0x0f)
+}
+
+// field 为完整的 struct field 字节 (含 head 与 EnStructEnd)
+fn patch_struct(field: &[u8], ops: &[(&[u8], &PatchOp)], out: &mut Vec<u8>) -> Result<(), DecodeErr> {
+    let mut reader = TarsReader::new(field);
+    // 跳过 Field 与 StructStart 事件
+    for _ in 0..2 {
+        match reader.next() {
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(e),
+            None => return Err(DecodeErr::NoEnoughDataErr),
+        }
+    }
+    patch_level(field, &mut reader, ops, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tars_decoder::*;
+    use tars_encoder::*;
+
+    #[test]
+    fn test_patch() {
+        let mut encoder = TarsEncoder::new().with_int_width(IntWidth::Fixed);
+        encoder.write_int32(0, 1).unwrap();
+        encoder.write_string(2, "foo").unwrap();
+        encoder.write_struct(3, &(1i32, "bar".to_string())).unwrap();
+        // 不认识的 field 以及定宽编码均按原字节保留
+        encoder.write_int64(9, 7).unwrap();
+        let buf = encoder.to_bytes();
+
+        let mut patcher = TarsPatcher::new(buf.clone());
+        patcher
+            .set(2, "hello")
+            .unwrap()
+            .set(1, &5i8)
+            .unwrap()
+            .remove(0)
+            .append(10, &true)
+            .unwrap()
+            .set_path(&[3, 1], "baz")
+            .unwrap()
+            .set_path(&[3, 2], &0.5f64)
+            .unwrap();
+        let patched = patcher.patch().unwrap();
+
+        let mut expected = TarsEncoder::new();
+        expected.write_int8(1, 5).unwrap();
+        expected.write_string(2, "hello").unwrap();
+        let mut sub = TarsEncoder::new().with_int_width(IntWidth::Fixed);
+        sub.write_int32(0, 1).unwrap();
+        let mut expected = expected.to_bytes_mut();
+        expected.extend_from_slice(b"\x3a");
+        expected.extend_from_slice(&sub.to_bytes());
+        expected.extend_from_slice(b"\x16\x03baz\x25\x3f\xe0\x00\x00\x00\x00\x00\x00\x0b");
+        expected.extend_from_slice(b"\x93\x00\x00\x00\x00\x00\x00\x00\x07\xa0\x01");
+        assert_eq!(patched, expected.freeze());
+
+        let mut de = TarsDecoder::from(&patched);
+        assert_eq!(de.read_int32(0, false, -1), Ok(-1));
+        assert_eq!(de.read_int8(1, true, 0), Ok(5));
+        assert_eq!(
+            de.read_struct(3, true, (0i32, String::new(), 0.0f64)),
+            Ok((1, "baz".to_string(), 0.5))
+        );
+
+        // 原 buffer 未变
+        assert_eq!(TarsPatcher::new(buf.clone()).patch(), Ok(buf));
+    }
+
+    #[test]
+    fn test_patch_in_order() {
+        let mut encoder = TarsEncoder::new();
+        encoder.write_struct(0, &(1i32, 2i32)).unwrap();
+        let buf = encoder.to_bytes();
+
+        // 后面的操作作用于前面操作的结果
+        let mut patcher = TarsPatcher::new(buf.clone());
+        patcher
+            .set_path(&[0, 0], &3i32)
+            .unwrap()
+            .set(0, &(4i32, 5i32))
+            .unwrap()
+            .set_path(&[0, 1], &6i32)
+            .unwrap()
+            .remove_path(&[0, 0]);
+        assert_eq!(patcher.patch(), Ok(Bytes::from(&b"\x0a\x10\x06\x0b"[..])));
+
+        let mut patcher = TarsPatcher::new(buf);
+        patcher.remove(0).set_path(&[0, 0], &1i32).unwrap();
+        assert_eq!(patcher.patch(), Err(DecodeErr::TarsTagNotFoundErr));
+    }
+
+    #[test]
+    fn test_patch_err() {
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int32(0, 1).unwrap();
+        let buf = encoder.to_bytes();
+
+        let mut patcher = TarsPatcher::new(buf.clone());
+        patcher.set_path(&[0, 1], &1i32).unwrap();
+        assert_eq!(patcher.patch(), Err(DecodeErr::MisMatchTarsTypeErr));
+
+        let mut patcher = TarsPatcher::new(buf.clone());
+        patcher.set_path(&[1, 1], &1i32).unwrap();
+        assert_eq!(patcher.patch(), Err(DecodeErr::TarsTagNotFoundErr));
+
+        let mut patcher = TarsPatcher::new(buf.slice_to(1));
+        patcher.remove(0);
+        assert_eq!(patcher.patch(), Err(DecodeErr::NoEnoughDataErr));
+    }
+}
@@ -0,0 +1,517 @@
+use bytes::{Buf, IntoBuf};
+use errors::DecodeErr;
+use tars_type::TarsTypeMark;
+use tars_type::TarsTypeMark::*;
+
+// 流式读取事件，Field 之后紧跟该 field 的值事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum TarsEvent<'a> {
+    Field { tag: u8, tars_type: TarsTypeMark },
+    Int(i64),
+    Float(f32),
+    Double(f64),
+    Str(&'a [u8]),
+    SimpleList(&'a [u8]),
+    ListStart(usize),
+    ListEnd,
+    MapStart(usize),
+    MapEnd,
+    StructStart,
+    StructEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    Struct,
+    List(usize),
+    // 剩余元素个数，key 与 value 各算一个
+    Map(usize),
+}
+
+#[derive(Debug)]
+pub struct TarsReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    stack: Vec<Frame>,
+    pending: Option<TarsTypeMark>,
+    finished: bool,
+}
+
+impl<'a> TarsReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        TarsReader {
+            buf,
+            pos: 0,
+            stack: vec![],
+            pending: None,
+            finished: false,
+        }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn accept<V>(self, visitor: &mut V) -> Result<(), DecodeErr>
+    where
+        V: TarsVisitor,
+    {
+        for event in self {
+            match event? {
+                TarsEvent::Field { tag, tars_type } => visitor.visit_field(tag, tars_type)?,
+                TarsEvent::Int(i) => visitor.visit_int(i)?,
+                TarsEvent::Float(f) => visitor.visit_float(f)?,
+                TarsEvent::Double(f) => visitor.visit_double(f)?,
+                TarsEvent::Str(s) => visitor.visit_str(s)?,
+                TarsEvent::SimpleList(b) => visitor.visit_simple_list(b)?,
+                TarsEvent::ListStart(len) => visitor.visit_list_start(len)?,
+                TarsEvent::ListEnd => visitor.visit_list_end()?,
+                TarsEvent::MapStart(len) => visitor.visit_map_start(len)?,
+                TarsEvent::MapEnd => visitor.visit_map_end()?,
+                TarsEvent::StructStart => visitor.visit_struct_start()?,
+                TarsEvent::StructEnd => visitor.visit_struct_end()?,
+            }
+        }
+        Ok(())
+    }
+
+    // 从 buf 的 pos 处开始读取，供 TarsDecoder 共用 head 与长度的解析
+    pub(crate) fn with_position(buf: &'a [u8], pos: usize) -> Self {
+        TarsReader {
+            pos,
+            ..TarsReader::new(buf)
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    pub(crate) fn take(&mut self, size: usize) -> Result<&'a [u8], DecodeErr> {
+        if self.remaining() < size {
+            Err(DecodeErr::NoEnoughDataErr)
+        } else {
+            let buf: &'a [u8] = self.buf;
+            let b = &buf[self.pos..self.pos + size];
+            self.pos += size;
+            Ok(b)
+        }
+    }
+
+    pub(crate) fn take_head(&mut self) -> Result<(u8, TarsTypeMark), DecodeErr> {
+        let b = self.take(1)?[0];
+        let tars_type = b & 0x0f;
+        let mut tag = (b & 0xf0) >> 4;
+        if tag == 15 {
+            tag = self.take(1)?[0];
+        }
+        // TarsTypeMark::from 会将未知类型当作 EnZero，此处需要报错
+        if tars_type > EnSimplelist.value() {
+            Err(DecodeErr::UnknownTarsTypeErr)
+        } else {
+            Ok((tag, TarsTypeMark::from(tars_type)))
+        }
+    }
+
+    pub(crate) fn take_int(&mut self, tars_type: &TarsTypeMark) -> Result<i64, DecodeErr> {
+        match *tars_type {
+            EnZero => Ok(0),
+            EnInt8 => Ok(i64::from(self.take(1)?.into_buf().get_i8())),
+            EnInt16 => Ok(i64::from(self.take(2)?.into_buf().get_i16_be())),
+            EnInt32 => Ok(i64::from(self.take(4)?.into_buf().get_i32_be())),
+            EnInt64 => Ok(self.take(8)?.into_buf().get_i64_be()),
+            _ => Err(DecodeErr::MisMatchTarsTypeErr),
+        }
+    }
+
+    // list/map/simplelist 的长度以 tag 0 的 int 写入
+    pub(crate) fn take_len(&mut self) -> Result<usize, DecodeErr> {
+        let (_, tars_type) = self.take_head()?;
+        match tars_type {
+            EnZero | EnInt8 | EnInt16 | EnInt32 => {
+                let len = self.take_int(&tars_type)?;
+                if len < 0 {
+                    Err(DecodeErr::NegativeLengthErr)
+                } else {
+                    Ok(len as usize)
+                }
+            }
+            _ => Err(DecodeErr::MisMatchTarsTypeErr),
+        }
+    }
+
+    // simplelist 的元素类型 head 与长度
+    fn take_simple_list_len(&mut self) -> Result<usize, DecodeErr> {
+        match self.take_head()? {
+            (_, EnInt8) | (_, EnInt16) | (_, EnInt32) => self.take_len(),
+            _ => Err(DecodeErr::WrongSimpleListTarsTypeErr),
+        }
+    }
+
+    // 跳过 head 之后的 field 内容，不产生事件
+    pub(crate) fn skip_value(&mut self, tars_type: TarsTypeMark) -> Result<(), DecodeErr> {
+        match tars_type {
+            EnZero | EnStructEnd => Ok(()),
+            EnInt8 => self.take(1).map(|_| ()),
+            EnInt16 => self.take(2).map(|_| ()),
+            EnInt32 | EnFloat => self.take(4).map(|_| ()),
+            EnInt64 | EnDouble => self.take(8).map(|_| ()),
+            EnString1 => {
+                let size = self.take(1)?[0] as usize;
+                self.take(size).map(|_| ())
+            }
+            EnString4 => {
+                let size = self.take(4)?.into_buf().get_u32_be() as usize;
+                self.take(size).map(|_| ())
+            }
+            EnMaps => {
+                let len = self.take_len()?;
+                for _ in 0..len * 2 {
+                    let (_, tars_type) = self.take_head()?;
+                    self.skip_value(tars_type)?;
+                }
+                Ok(())
+            }
+            EnList => {
+                let len = self.take_len()?;
+                for _ in 0..len {
+                    let (_, tars_type) = self.take_head()?;
+                    self.skip_value(tars_type)?;
+                }
+                Ok(())
+            }
+            EnStructBegin => loop {
+                match self.take_head()? {
+                    (_, EnStructEnd) => return Ok(()),
+                    (_, tars_type) => self.skip_value(tars_type)?,
+                }
+            },
+            EnSimplelist => {
+                let size = self.take_simple_list_len()?;
+                self.take(size).map(|_| ())
+            }
+        }
+    }
+
+    fn take_value(&mut self, tars_type: TarsTypeMark) -> Result<TarsEvent<'a>, DecodeErr> {
+        match tars_type {
+            EnZero | EnInt8 | EnInt16 | EnInt32 | EnInt64 => {
+                Ok(TarsEvent::Int(self.take_int(&tars_type)?))
+            }
+            EnFloat => Ok(TarsEvent::Float(self.take(4)?.into_buf().get_f32_be())),
+            EnDouble => Ok(TarsEvent::Double(self.take(8)?.into_buf().get_f64_be())),
+            EnString1 => {
+                let size = self.take(1)?[0] as usize;
+                Ok(TarsEvent::Str(self.take(size)?))
+            }
+            EnString4 => {
+                let size = self.take(4)?.into_buf().get_u32_be() as usize;
+                Ok(TarsEvent::Str(self.take(size)?))
+            }
+            EnMaps => {
+                let len = self.take_len()?;
+                self.stack.push(Frame::Map(len * 2));
+                Ok(TarsEvent::MapStart(len))
+            }
+            EnList => {
+                let len = self.take_len()?;
+                self.stack.push(Frame::List(len));
+                Ok(TarsEvent::ListStart(len))
+            }
+            EnStructBegin => {
+                self.stack.push(Frame::Struct);
+                Ok(TarsEvent::StructStart)
+            }
+            EnSimplelist => {
+                let size = self.take_simple_list_len()?;
+                Ok(TarsEvent::SimpleList(self.take(size)?))
+            }
+            EnStructEnd => Err(DecodeErr::UnbalancedStructErr),
+        }
+    }
+
+    fn next_event(&mut self) -> Option<Result<TarsEvent<'a>, DecodeErr>> {
+        if let Some(tars_type) = self.pending.take() {
+            return Some(self.take_value(tars_type));
+        }
+
+        match self.stack.last_mut() {
+            Some(Frame::List(0)) => {
+                self.stack.pop();
+                return Some(Ok(TarsEvent::ListEnd));
+            }
+            Some(Frame::Map(0)) => {
+                self.stack.pop();
+                return Some(Ok(TarsEvent::MapEnd));
+            }
+            Some(Frame::List(n)) | Some(Frame::Map(n)) => *n -= 1,
+            Some(Frame::Struct) => {}
+            None => if self.remaining() == 0 {
+                return None;
+            },
+        }
+
+        match self.take_head() {
+            Ok((_, EnStructEnd)) => match self.stack.last() {
+                Some(Frame::Struct) => {
+                    self.stack.pop();
+                    Some(Ok(TarsEvent::StructEnd))
+                }
+                _ => Some(Err(DecodeErr::UnbalancedStructErr)),
+            },
+            Ok((tag, tars_type)) => {
+                self.pending = Some(tars_type.clone());
+                Some(Ok(TarsEvent::Field { tag, tars_type }))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<'a> Iterator for TarsReader<'a> {
+    type Item = Result<TarsEvent<'a>, DecodeErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let event = self.next_event();
+        match event {
+            None | Some(Err(_)) => self.finished = true,
+            _ => {}
+        }
+        event
+    }
+}
+
+// 由 TarsReader/TarsDecoder 驱动，按需覆盖
+pub trait TarsVisitor {
+    fn visit_field(&mut self, _tag: u8, _tars_type: TarsTypeMark) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_int(&mut self, _ele: i64) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_float(&mut self, _ele: f32) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_double(&mut self, _ele: f64) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_str(&mut self, _ele: &[u8]) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_simple_list(&mut self, _ele: &[u8]) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_list_start(&mut self, _len: usize) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_list_end(&mut self) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_map_start(&mut self, _len: usize) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_map_end(&mut self) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_struct_start(&mut self) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+
+    fn visit_struct_end(&mut self) -> Result<(), DecodeErr> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tars_decoder::{TarsDecodeNormalTrait, TarsDecoder};
+    use tars_encoder::*;
+
+    #[test]
+    fn test_read_events() {
+        let mut map: BTreeMap<String, i32> = BTreeMap::new();
+        map.insert("hello".to_string(), 32);
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int32(0, 0).unwrap();
+        encoder.write_int64(1, -129).unwrap();
+        encoder.write_string(2, "foo").unwrap();
+        encoder.write_list(3, &[1i32, 70000]).unwrap();
+        encoder.write_map(4, &map).unwrap();
+        encoder.write_list(5, &[1i8, 2]).unwrap();
+        encoder.write_double(6, 0.5).unwrap();
+        let buf = encoder.to_bytes();
+
+        let events: Vec<TarsEvent> = TarsReader::new(&buf).map(|e| e.unwrap()).collect();
+        assert_eq!(
+            events,
+            vec![
+                TarsEvent::Field { tag: 0, tars_type: EnZero },
+                TarsEvent::Int(0),
+                TarsEvent::Field { tag: 1, tars_type: EnInt16 },
+                TarsEvent::Int(-129),
+                TarsEvent::Field { tag: 2, tars_type: EnString1 },
+                TarsEvent::Str(&b"foo"[..]),
+                TarsEvent::Field { tag: 3, tars_type: EnList },
+                TarsEvent::ListStart(2),
+                TarsEvent::Field { tag: 0, tars_type: EnInt8 },
+                TarsEvent::Int(1),
+                TarsEvent::Field { tag: 0, tars_type: EnInt32 },
+                TarsEvent::Int(70000),
+                TarsEvent::ListEnd,
+                TarsEvent::Field { tag: 4, tars_type: EnMaps },
+                TarsEvent::MapStart(1),
+                TarsEvent::Field { tag: 0, tars_type: EnString1 },
+                TarsEvent::Str(&b"hello"[..]),
+                TarsEvent::Field { tag: 1, tars_type: EnInt8 },
+                TarsEvent::Int(32),
+                TarsEvent::MapEnd,
+                TarsEvent::Field { tag: 5, tars_type: EnSimplelist },
+                TarsEvent::SimpleList(&b"\x01\x02"[..]),
+                TarsEvent::Field { tag: 6, tars_type: EnDouble },
+                TarsEvent::Double(0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_struct_events() {
+        let b: [u8; 6] = [0x1a, 0x00, 0x7f, 0x1c, 0x0b, 0x2c];
+        let events: Vec<TarsEvent> = TarsReader::new(&b[..]).map(|e| e.unwrap()).collect();
+        assert_eq!(
+            events,
+            vec![
+                TarsEvent::Field { tag: 1, tars_type: EnStructBegin },
+                TarsEvent::StructStart,
+                TarsEvent::Field { tag: 0, tars_type: EnInt8 },
+                TarsEvent::Int(127),
+                TarsEvent::Field { tag: 1, tars_type: EnZero },
+                TarsEvent::Int(0),
+                TarsEvent::StructEnd,
+                TarsEvent::Field { tag: 2, tars_type: EnZero },
+                TarsEvent::Int(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_events_err() {
+        // 未结束的 struct
+        let b: [u8; 3] = [0x1a, 0x00, 0x7f];
+        let last = TarsReader::new(&b[..]).last().unwrap();
+        assert_eq!(last, Err(DecodeErr::NoEnoughDataErr));
+
+        // 未知类型
+        let b: [u8; 2] = [0x0e, 0x00];
+        let last = TarsReader::new(&b[..]).last().unwrap();
+        assert_eq!(last, Err(DecodeErr::UnknownTarsTypeErr));
+
+        // 多余的 struct end
+        let b: [u8; 2] = [0x0c, 0x0b];
+        let last = TarsReader::new(&b[..]).last().unwrap();
+        assert_eq!(last, Err(DecodeErr::UnbalancedStructErr));
+
+        // 负数长度
+        let b: [u8; 3] = [0x09, 0x00, 0xff];
+        let last = TarsReader::new(&b[..]).last().unwrap();
+        assert_eq!(last, Err(DecodeErr::NegativeLengthErr));
+
+        // string 长度不足
+        let b: [u8; 3] = [0x06, 0x05, b'a'];
+        let mut reader = TarsReader::new(&b[..]);
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(reader.next(), Some(Err(DecodeErr::NoEnoughDataErr)));
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn test_decoder_skip_errors() {
+        // TarsDecoder 跳过 field 时与 TarsReader 共用解析，错误一致
+        let b: [u8; 4] = [0x0e, 0x00, 0x10, 0x01];
+        let mut decoder = TarsDecoder::from(&b[..]);
+        assert_eq!(decoder.read_int8(1, true, 0), Err(DecodeErr::UnknownTarsTypeErr));
+
+        let b: [u8; 5] = [0x09, 0x00, 0xff, 0x10, 0x01];
+        let mut decoder = TarsDecoder::from(&b[..]);
+        assert_eq!(decoder.read_int8(1, true, 0), Err(DecodeErr::NegativeLengthErr));
+
+        // simplelist 的元素类型不是 int 时跳过同样报错
+        let b: [u8; 5] = [0x0d, 0x06, 0x00, 0x10, 0x01];
+        let mut decoder = TarsDecoder::from(&b[..]);
+        assert_eq!(
+            decoder.read_int8(1, true, 0),
+            Err(DecodeErr::WrongSimpleListTarsTypeErr)
+        );
+        let mut reader = TarsReader::new(&b[..]);
+        reader.next();
+        assert_eq!(reader.next(), Some(Err(DecodeErr::WrongSimpleListTarsTypeErr)));
+    }
+
+    #[derive(Default)]
+    struct CountVisitor {
+        fields: usize,
+        ints: i64,
+        structs: usize,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl TarsVisitor for CountVisitor {
+        fn visit_field(&mut self, _tag: u8, _tars_type: TarsTypeMark) -> Result<(), DecodeErr> {
+            self.fields += 1;
+            Ok(())
+        }
+
+        fn visit_int(&mut self, ele: i64) -> Result<(), DecodeErr> {
+            self.ints += ele;
+            Ok(())
+        }
+
+        fn visit_struct_start(&mut self) -> Result<(), DecodeErr> {
+            self.structs += 1;
+            self.depth += 1;
+            if self.depth > self.max_depth {
+                self.max_depth = self.depth;
+            }
+            Ok(())
+        }
+
+        fn visit_struct_end(&mut self) -> Result<(), DecodeErr> {
+            self.depth -= 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let b: [u8; 11] = [0x1a, 0x0a, 0x00, 0x7f, 0x0b, 0x10, 0x03, 0x0b, 0x20, 0x04, 0x6c];
+        let mut decoder = TarsDecoder::from(&b[..]);
+        let mut visitor = CountVisitor::default();
+        decoder.accept(&mut visitor).unwrap();
+
+        assert_eq!(visitor.fields, 6);
+        assert_eq!(visitor.ints, 127 + 3 + 4);
+        assert_eq!(visitor.structs, 2);
+        assert_eq!(visitor.max_depth, 2);
+        assert_eq!(visitor.depth, 0);
+    }
+}
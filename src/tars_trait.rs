@@ -1,8 +1,13 @@
 use bytes::Bytes;
 use errors::{DecodeErr, EncodeErr};
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::rc::Rc;
+use std::sync::Arc;
 use tars_decoder::TarsDecoder;
 use tars_encoder::TarsEncoder;
+use tars_type::SimpleList;
 
 // Tars Struct 需要实现此trait
 pub trait StructFromTars {
@@ -31,100 +36,251 @@ pub trait EnumToI32 {
 // Tars 所有类型需要实现此trait
 pub trait ClassName {
     fn _class_name() -> String;
+
+    // 基础类型与 Bytes 返回字面量，不分配内存；容器等类型在每次调用时拼接
+    fn _class_name_cow() -> Cow<'static, str> {
+        Cow::Owned(Self::_class_name())
+    }
+}
+
+macro_rules! impl_class_name {
+    ($($t:ty => $name:expr),*) => {$(
+        impl ClassName for $t {
+            fn _class_name() -> String {
+                String::from($name)
+            }
+
+            fn _class_name_cow() -> Cow<'static, str> {
+                Cow::Borrowed($name)
+            }
+        }
+    )*}
 }
 
-impl ClassName for bool {
+impl_class_name!(
+    bool => "bool",
+    i8 => "char",
+    i16 => "short",
+    i32 => "int32",
+    i64 => "int64",
+    u8 => "short",
+    u16 => "int32",
+    u32 => "int64",
+    u64 => "int64",
+    f32 => "float",
+    f64 => "double",
+    String => "string",
+    str => "string"
+);
+
+impl<K, V> ClassName for BTreeMap<K, V>
+where
+    K: ClassName + Ord,
+    V: ClassName,
+{
     fn _class_name() -> String {
-        String::from("bool")
+        String::from("map<")
+            + &K::_class_name()
+            + &String::from(",")
+            + &V::_class_name()
+            + &String::from(">")
     }
 }
 
-impl ClassName for i8 {
+impl<K, V, S> ClassName for HashMap<K, V, S>
+where
+    K: ClassName + Eq + Hash,
+    V: ClassName,
+    S: BuildHasher,
+{
     fn _class_name() -> String {
-        String::from("char")
+        String::from("map<")
+            + &K::_class_name()
+            + &String::from(",")
+            + &V::_class_name()
+            + &String::from(">")
     }
 }
 
-impl ClassName for i16 {
+impl<T> ClassName for Vec<T>
+where
+    T: ClassName,
+{
     fn _class_name() -> String {
-        String::from("short")
+        // List not list
+        String::from("List<") + &T::_class_name() + &String::from(">")
     }
 }
 
-impl ClassName for i32 {
+impl ClassName for Bytes {
     fn _class_name() -> String {
-        String::from("int32")
+        String::from("list<byte>")
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        Cow::Borrowed("list<byte>")
     }
 }
 
-impl ClassName for i64 {
+impl ClassName for SimpleList {
     fn _class_name() -> String {
-        String::from("int64")
+        Bytes::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        Bytes::_class_name_cow()
     }
 }
 
-impl ClassName for u8 {
+impl<T> ClassName for VecDeque<T>
+where
+    T: ClassName,
+{
     fn _class_name() -> String {
-        String::from("short")
+        Vec::<T>::_class_name()
     }
 }
 
-impl ClassName for u16 {
+impl<T> ClassName for BTreeSet<T>
+where
+    T: ClassName + Ord,
+{
     fn _class_name() -> String {
-        String::from("int32")
+        Vec::<T>::_class_name()
     }
 }
 
-impl ClassName for u32 {
+impl<T, S> ClassName for HashSet<T, S>
+where
+    T: ClassName + Eq + Hash,
+    S: BuildHasher,
+{
     fn _class_name() -> String {
-        String::from("int64")
+        Vec::<T>::_class_name()
     }
 }
 
-impl ClassName for f32 {
+impl<T> ClassName for [T]
+where
+    T: ClassName,
+{
     fn _class_name() -> String {
-        String::from("float")
+        Vec::<T>::_class_name()
     }
 }
 
-impl ClassName for f64 {
+impl<T> ClassName for &T
+where
+    T: ClassName + ?Sized,
+{
     fn _class_name() -> String {
-        String::from("double")
+        T::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        T::_class_name_cow()
     }
 }
 
-impl ClassName for String {
+impl<T> ClassName for Box<T>
+where
+    T: ClassName + ?Sized,
+{
     fn _class_name() -> String {
-        String::from("string")
+        T::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        T::_class_name_cow()
     }
 }
 
-impl<K, V> ClassName for BTreeMap<K, V>
+impl<T> ClassName for Rc<T>
 where
-    K: ClassName + Ord,
-    V: ClassName,
+    T: ClassName + ?Sized,
 {
     fn _class_name() -> String {
-        String::from("map<")
-            + &K::_class_name()
-            + &String::from(",")
-            + &V::_class_name()
-            + &String::from(">")
+        T::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        T::_class_name_cow()
     }
 }
 
-impl<T> ClassName for Vec<T>
+impl<T> ClassName for Arc<T>
 where
-    T: ClassName,
+    T: ClassName + ?Sized,
 {
     fn _class_name() -> String {
-        // List not list
-        String::from("List<") + &T::_class_name() + &String::from(">")
+        T::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        T::_class_name_cow()
     }
 }
 
-impl ClassName for Bytes {
+impl<'a, T> ClassName for Cow<'a, T>
+where
+    T: ClassName + ToOwned + ?Sized,
+{
     fn _class_name() -> String {
-        String::from("list<byte>")
+        T::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        T::_class_name_cow()
     }
 }
+
+impl<T, const N: usize> ClassName for [T; N]
+where
+    T: ClassName,
+{
+    default fn _class_name() -> String {
+        Vec::<T>::_class_name()
+    }
+
+    default fn _class_name_cow() -> Cow<'static, str> {
+        Cow::Owned(Self::_class_name())
+    }
+}
+
+impl<const N: usize> ClassName for [u8; N] {
+    fn _class_name() -> String {
+        Bytes::_class_name()
+    }
+
+    fn _class_name_cow() -> Cow<'static, str> {
+        Bytes::_class_name_cow()
+    }
+}
+
+// tuple 没有对应的 tars 类型，以 tuple<A,B,...> 表示
+macro_rules! impl_tuple_class_name {
+    ($($name:ident),+) => {
+        impl<$($name),+> ClassName for ($($name,)+)
+        where
+            $($name: ClassName),+
+        {
+            fn _class_name() -> String {
+                let names: Vec<String> = vec![$($name::_class_name()),+];
+                String::from("tuple<") + &names.join(",") + &String::from(">")
+            }
+        }
+    };
+}
+
+impl_tuple_class_name!(A);
+impl_tuple_class_name!(A, B);
+impl_tuple_class_name!(A, B, C);
+impl_tuple_class_name!(A, B, C, D);
+impl_tuple_class_name!(A, B, C, D, E);
+impl_tuple_class_name!(A, B, C, D, E, F);
+impl_tuple_class_name!(A, B, C, D, E, F, G);
+impl_tuple_class_name!(A, B, C, D, E, F, G, H);
+impl_tuple_class_name!(A, B, C, D, E, F, G, H, I);
+impl_tuple_class_name!(A, B, C, D, E, F, G, H, I, J);
+impl_tuple_class_name!(A, B, C, D, E, F, G, H, I, J, K);
+impl_tuple_class_name!(A, B, C, D, E, F, G, H, I, J, K, L);
@@ -1,3 +1,5 @@
+use std::ops::{Deref, DerefMut};
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TarsTypeMark {
     EnInt8 = 0,
@@ -68,3 +70,34 @@ impl From<u8> for ProtocolVersion {
         }
     }
 }
+
+// Vec<u8> 默认以 list<short> 编码，SimpleList 以 simplelist 编码，
+// 对应 C++ 的 vector<char>/vector<unsigned char>
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SimpleList(pub Vec<u8>);
+
+impl Deref for SimpleList {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for SimpleList {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u8>> for SimpleList {
+    fn from(v: Vec<u8>) -> Self {
+        SimpleList(v)
+    }
+}
+
+impl From<SimpleList> for Vec<u8> {
+    fn from(v: SimpleList) -> Self {
+        v.0
+    }
+}
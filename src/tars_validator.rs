@@ -0,0 +1,201 @@
+use bytes::Bytes;
+use errors::DecodeErr;
+use std::error::Error;
+use std::fmt;
+use tars_reader::{TarsEvent, TarsReader};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValidateOptions {
+    max_depth: Option<usize>,
+    single_field: bool,
+}
+
+impl ValidateOptions {
+    pub fn new() -> Self {
+        ValidateOptions::default()
+    }
+
+    // 限制 struct/list/map 的嵌套层数
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    // buffer 只包含一个顶层 field (如 TupUniAttribute 中的值)，其后的数据视为多余
+    pub fn with_single_field(mut self, single_field: bool) -> Self {
+        self.single_field = single_field;
+        self
+    }
+}
+
+// offset 为出错元素在 buffer 中的起始位置
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValidateErr {
+    pub offset: usize,
+    pub err: DecodeErr,
+}
+
+impl fmt::Display for ValidateErr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.err, self.offset)
+    }
+}
+
+impl Error for ValidateErr {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Struct,
+    List,
+    Map,
+}
+
+// 无 schema 遍历整个 buffer，返回第一个错误
+pub fn validate(buf: &Bytes, options: ValidateOptions) -> Result<(), ValidateErr> {
+    let mut reader = TarsReader::new(buf);
+    let mut stack: Vec<Container> = vec![];
+
+    if options.single_field && buf.is_empty() {
+        return Err(ValidateErr {
+            offset: 0,
+            err: DecodeErr::NoEnoughDataErr,
+        });
+    }
+
+    loop {
+        let offset = reader.position();
+        let event = match reader.next() {
+            Some(Ok(event)) => event,
+            Some(Err(e)) => {
+                // 读到 buffer 末尾时 struct 仍未结束
+                let err = if e == DecodeErr::NoEnoughDataErr
+                    && offset == buf.len()
+                    && stack.last() == Some(&Container::Struct)
+                {
+                    DecodeErr::UnbalancedStructErr
+                } else {
+                    e
+                };
+                return Err(ValidateErr { offset, err });
+            }
+            None => return Ok(()),
+        };
+
+        match event {
+            TarsEvent::Field { .. } => continue,
+            TarsEvent::StructStart => stack.push(Container::Struct),
+            TarsEvent::ListStart(_) => stack.push(Container::List),
+            TarsEvent::MapStart(_) => stack.push(Container::Map),
+            TarsEvent::StructEnd | TarsEvent::ListEnd | TarsEvent::MapEnd => {
+                stack.pop();
+            }
+            _ => {}
+        }
+
+        if let Some(max_depth) = options.max_depth {
+            if stack.len() > max_depth {
+                return Err(ValidateErr {
+                    offset,
+                    err: DecodeErr::TooDeepErr,
+                });
+            }
+        }
+
+        if options.single_field && stack.is_empty() && reader.position() < buf.len() {
+            return Err(ValidateErr {
+                offset: reader.position(),
+                err: DecodeErr::TrailingDataErr,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tars_encoder::*;
+
+    #[test]
+    fn test_validate() {
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int32(0, 70000).unwrap();
+        encoder.write_string(1, "foo").unwrap();
+        encoder.write_list(2, &[vec![1i64]]).unwrap();
+        encoder.write_bytes(3, b"bar").unwrap();
+        let buf = encoder.to_bytes();
+        assert_eq!(validate(&buf, ValidateOptions::new()), Ok(()));
+        assert_eq!(validate(&Bytes::new(), ValidateOptions::new()), Ok(()));
+
+        // 截断
+        let b = buf.slice_to(buf.len() - 1);
+        assert_eq!(
+            validate(&b, ValidateOptions::new()),
+            Err(ValidateErr {
+                offset: 19,
+                err: DecodeErr::NoEnoughDataErr,
+            })
+        );
+
+        // list 嵌套两层
+        assert_eq!(
+            validate(&buf, ValidateOptions::new().with_max_depth(1)),
+            Err(ValidateErr {
+                offset: 14,
+                err: DecodeErr::TooDeepErr,
+            })
+        );
+        assert_eq!(validate(&buf, ValidateOptions::new().with_max_depth(2)), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_err() {
+        // 未知类型
+        let b = Bytes::from(&b"\x00\x01\x1e"[..]);
+        assert_eq!(
+            validate(&b, ValidateOptions::new()),
+            Err(ValidateErr {
+                offset: 2,
+                err: DecodeErr::UnknownTarsTypeErr,
+            })
+        );
+
+        // struct 未结束与多余的 struct end
+        let b = Bytes::from(&b"\x0a\x10\x01"[..]);
+        assert_eq!(
+            validate(&b, ValidateOptions::new()),
+            Err(ValidateErr {
+                offset: 3,
+                err: DecodeErr::UnbalancedStructErr,
+            })
+        );
+        let b = Bytes::from(&b"\x0a\x0b\x0b"[..]);
+        assert_eq!(
+            validate(&b, ValidateOptions::new()),
+            Err(ValidateErr {
+                offset: 2,
+                err: DecodeErr::UnbalancedStructErr,
+            })
+        );
+
+        // simple list 负数长度
+        let b = Bytes::from(&b"\x0d\x00\x00\xfe"[..]);
+        assert_eq!(
+            validate(&b, ValidateOptions::new()),
+            Err(ValidateErr {
+                offset: 1,
+                err: DecodeErr::NegativeLengthErr,
+            })
+        );
+
+        // 多余数据
+        let b = Bytes::from(&b"\x0a\x10\x01\x0b\x00\x02"[..]);
+        assert_eq!(validate(&b, ValidateOptions::new()), Ok(()));
+        assert_eq!(
+            validate(&b, ValidateOptions::new().with_single_field(true)),
+            Err(ValidateErr {
+                offset: 4,
+                err: DecodeErr::TrailingDataErr,
+            })
+        );
+    }
+}
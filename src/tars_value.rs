@@ -0,0 +1,320 @@
+use bytes::Bytes;
+use errors::{DecodeErr, EncodeErr};
+use std::fmt;
+use tars_encoder::{EncodeTars, TarsEncoder};
+use tars_reader::{TarsEvent, TarsReader};
+
+// 无 schema 的 tars 值
+// EnZero 与各宽度整数均读为 Int，string 不保证是 utf8，以 Bytes 保存
+#[derive(Debug, Clone, PartialEq)]
+pub enum TarsValue {
+    Int(i64),
+    Float(f32),
+    Double(f64),
+    String(Bytes),
+    SimpleList(Bytes),
+    List(Vec<TarsValue>),
+    Map(Vec<(TarsValue, TarsValue)>),
+    Struct(Vec<(u8, TarsValue)>),
+}
+
+// field 所在路径，顶层为 tag，用于 diff 与 TarsDecoder::project
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    Tag(u8),
+    Index(usize),
+    Key(TarsValue),
+}
+
+impl TarsValue {
+    // 解析 buffer 中的全部顶层 field
+    pub fn from_fields(buf: &[u8]) -> Result<Vec<(u8, TarsValue)>, DecodeErr> {
+        let mut reader = TarsReader::new(buf);
+        let mut fields = vec![];
+        while let Some(event) = reader.next() {
+            match event? {
+                TarsEvent::Field { tag, .. } => fields.push((tag, take_value(&mut reader)?)),
+                _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+            }
+        }
+        Ok(fields)
+    }
+
+    // 解析单个 field (head 与内容)，返回 tag 与值
+    pub fn from_field(buf: &[u8]) -> Result<(u8, TarsValue), DecodeErr> {
+        let mut reader = TarsReader::new(buf);
+        let tag = match next_event(&mut reader)? {
+            TarsEvent::Field { tag, .. } => tag,
+            _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+        };
+        let value = take_value(&mut reader)?;
+        match reader.next() {
+            None => Ok((tag, value)),
+            Some(Err(e)) => Err(e),
+            Some(Ok(_)) => Err(DecodeErr::TrailingDataErr),
+        }
+    }
+
+    pub fn encode_fields(fields: &[(u8, TarsValue)]) -> Result<Bytes, EncodeErr> {
+        let mut encoder = TarsEncoder::new();
+        for &(tag, ref value) in fields {
+            value._encode(&mut encoder, tag)?;
+        }
+        Ok(encoder.to_bytes())
+    }
+
+    // struct field 按 tag 升序，map 按 key 编码后的字节升序
+    pub fn canonicalize(&mut self) -> Result<(), EncodeErr> {
+        match *self {
+            TarsValue::List(ref mut v) => {
+                for ele in v.iter_mut() {
+                    ele.canonicalize()?;
+                }
+            }
+            TarsValue::Map(ref mut entries) => {
+                let mut keyed = Vec::with_capacity(entries.len());
+                for (mut key, mut value) in entries.drain(..) {
+                    key.canonicalize()?;
+                    value.canonicalize()?;
+                    let key_bytes = TarsEncoder::individual_encode(&key)?;
+                    keyed.push((key_bytes, key, value));
+                }
+                keyed.sort_by(|a, b| a.0.cmp(&b.0));
+                entries.extend(keyed.into_iter().map(|(_, key, value)| (key, value)));
+            }
+            TarsValue::Struct(ref mut fields) => canonicalize_fields(fields)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// 用于日志与 diff 报告，string 按 utf8 有损显示，simplelist 显示为 hex
+impl fmt::Display for TarsValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TarsValue::Int(i) => write!(f, "{}", i),
+            TarsValue::Float(v) => write!(f, "{}", v),
+            TarsValue::Double(v) => write!(f, "{}", v),
+            TarsValue::String(ref s) => write!(f, "{:?}", String::from_utf8_lossy(s)),
+            TarsValue::SimpleList(ref b) => {
+                write!(f, "0x")?;
+                for byte in b.iter() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            TarsValue::List(ref v) => {
+                write!(f, "[")?;
+                for (i, ele) in v.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", ele)?;
+                }
+                write!(f, "]")
+            }
+            TarsValue::Map(ref entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            TarsValue::Struct(ref fields) => {
+                write!(f, "{{")?;
+                for (i, (tag, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", tag, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+pub fn canonicalize_fields(fields: &mut [(u8, TarsValue)]) -> Result<(), EncodeErr> {
+    for &mut (_, ref mut value) in fields.iter_mut() {
+        value.canonicalize()?;
+    }
+    // 稳定排序，重复 tag 保持原有顺序
+    fields.sort_by_key(|f| f.0);
+    Ok(())
+}
+
+// 重新编码为规范形式：整数最小宽度，0 写为 EnZero，能用 String1 时不用 String4，
+// struct field 按 tag 排序，map 按 key 字节排序
+pub fn canonicalize(buf: &Bytes) -> Result<Bytes, DecodeErr> {
+    let mut fields = TarsValue::from_fields(buf)?;
+    canonicalize_fields(&mut fields)?;
+    Ok(TarsValue::encode_fields(&fields)?)
+}
+
+// 非法 buffer 返回 false
+pub fn is_canonical(buf: &Bytes) -> bool {
+    match canonicalize(buf) {
+        Ok(b) => b == *buf,
+        Err(_) => false,
+    }
+}
+
+fn next_event<'a>(reader: &mut TarsReader<'a>) -> Result<TarsEvent<'a>, DecodeErr> {
+    match reader.next() {
+        Some(event) => event,
+        None => Err(DecodeErr::NoEnoughDataErr),
+    }
+}
+
+// 读取 Field 事件之后的值
+fn take_value(reader: &mut TarsReader) -> Result<TarsValue, DecodeErr> {
+    match next_event(reader)? {
+        TarsEvent::Int(i) => Ok(TarsValue::Int(i)),
+        TarsEvent::Float(f) => Ok(TarsValue::Float(f)),
+        TarsEvent::Double(f) => Ok(TarsValue::Double(f)),
+        TarsEvent::Str(s) => Ok(TarsValue::String(Bytes::from(s))),
+        TarsEvent::SimpleList(b) => Ok(TarsValue::SimpleList(Bytes::from(b))),
+        TarsEvent::ListStart(_) => {
+            let mut v = vec![];
+            loop {
+                match next_event(reader)? {
+                    TarsEvent::ListEnd => return Ok(TarsValue::List(v)),
+                    TarsEvent::Field { .. } => v.push(take_value(reader)?),
+                    _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+                }
+            }
+        }
+        TarsEvent::MapStart(_) => {
+            let mut entries = vec![];
+            loop {
+                match next_event(reader)? {
+                    TarsEvent::MapEnd => return Ok(TarsValue::Map(entries)),
+                    TarsEvent::Field { .. } => {
+                        let key = take_value(reader)?;
+                        match next_event(reader)? {
+                            TarsEvent::Field { .. } => {}
+                            _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+                        }
+                        entries.push((key, take_value(reader)?));
+                    }
+                    _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+                }
+            }
+        }
+        TarsEvent::StructStart => {
+            let mut fields = vec![];
+            loop {
+                match next_event(reader)? {
+                    TarsEvent::StructEnd => return Ok(TarsValue::Struct(fields)),
+                    TarsEvent::Field { tag, .. } => fields.push((tag, take_value(reader)?)),
+                    _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+                }
+            }
+        }
+        _ => Err(DecodeErr::MisMatchTarsTypeErr),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tars_decoder::*;
+    use tars_encoder::*;
+
+    #[test]
+    fn test_from_fields() {
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), vec![1i32]);
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int32(0, 70000).unwrap();
+        encoder.write_string(1, "foo").unwrap();
+        encoder.write_hash_map(2, &map).unwrap();
+        encoder.write_bytes(3, b"bar").unwrap();
+        encoder.write_struct(4, &(1.5f32, 0.25f64)).unwrap();
+        let buf = encoder.to_bytes();
+
+        let fields = TarsValue::from_fields(&buf).unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                (0, TarsValue::Int(70000)),
+                (1, TarsValue::String(Bytes::from(&b"foo"[..]))),
+                (
+                    2,
+                    TarsValue::Map(vec![(
+                        TarsValue::String(Bytes::from(&b"a"[..])),
+                        TarsValue::List(vec![TarsValue::Int(1)]),
+                    )]),
+                ),
+                (3, TarsValue::SimpleList(Bytes::from(&b"bar"[..]))),
+                (
+                    4,
+                    TarsValue::Struct(vec![(0, TarsValue::Float(1.5)), (1, TarsValue::Double(0.25))]),
+                ),
+            ]
+        );
+        assert_eq!(TarsValue::encode_fields(&fields).unwrap(), buf);
+
+        let mut de = TarsDecoder::from(&buf);
+        let v = TarsValue::_decode(&mut de, 2).unwrap();
+        assert_eq!(v, fields[2].1);
+
+        let mut encoder = TarsEncoder::new();
+        fields[4].1._encode(&mut encoder, 7).unwrap();
+        assert_eq!(
+            TarsValue::from_field(&encoder.to_bytes()),
+            Ok((7, fields[4].1.clone()))
+        );
+        assert_eq!(
+            TarsValue::from_field(&buf),
+            Err(DecodeErr::TrailingDataErr)
+        );
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let mut map = HashMap::new();
+        for i in 0..16 {
+            map.insert(i, i);
+        }
+
+        let mut encoder = TarsEncoder::new();
+        encoder.write_hash_map(1, &map).unwrap();
+        encoder.write_int32(0, 3).unwrap();
+        let buf = encoder.to_bytes();
+
+        // map 按 key 编码后的字节排序，0 编码为 EnZero 排在最后
+        let entries = (1..16)
+            .chain(Some(0))
+            .map(|i| (TarsValue::Int(i), TarsValue::Int(i)))
+            .collect();
+        let mut encoder = TarsEncoder::new();
+        encoder.write_int32(0, 3).unwrap();
+        TarsValue::Map(entries)._encode(&mut encoder, 1).unwrap();
+        let expected = encoder.to_bytes();
+
+        assert!(!is_canonical(&buf));
+        assert_eq!(canonicalize(&buf), Ok(expected.clone()));
+        assert!(is_canonical(&expected));
+
+        // 定宽整数、string4、struct 内 tag 乱序
+        let b = Bytes::from(
+            &b"\x02\x00\x00\x00\x00\x17\x00\x00\x00\x02hi\x2a\x10\x01\x00\x00\x0b"[..],
+        );
+        assert!(!is_canonical(&b));
+        assert_eq!(
+            canonicalize(&b),
+            Ok(Bytes::from(&b"\x0c\x16\x02hi\x2a\x0c\x10\x01\x0b"[..]))
+        );
+
+        // 非法 buffer
+        assert!(!is_canonical(&Bytes::from(&b"\x0a\x0c"[..])));
+        assert!(is_canonical(&Bytes::new()));
+    }
+}
@@ -1,21 +1,106 @@
 use bytes::Bytes;
 use errors::{DecodeErr, EncodeErr};
-use std::collections::BTreeMap;
+use std::any::{Any, TypeId};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use tars_class_name::{normalize_class_name, ClassNameDialect};
 use tars_decoder::{DecodeTars, TarsDecoder};
 use tars_encoder::{EncodeTars, TarsEncoder};
 
+use tars_display::{TarsDisplay, TarsDisplayer};
+use tars_reader::{TarsEvent, TarsReader};
 use tars_trait::ClassName;
 use tars_type::ProtocolVersion;
+use tars_type::TarsTypeMark::{EnMaps, EnSimplelist};
 
 type SimpleTupMap = BTreeMap<String, Bytes>;
 type ComplexTupMap = BTreeMap<String, BTreeMap<String, Bytes>>;
 
+// 以字段名为 key 读写 TupUniAttribute 的 struct 需要实现此 trait，可由 tars-stream-derive 生成
+// Option 字段为可选，读取时不存在为 None，写入时 None 不写；其余字段不存在时返回 TupKeyNotFoundErr
+pub trait TupAttribute {
+    fn _write_to(&self, uni: &mut TupUniAttribute) -> Result<(), EncodeErr>;
+    fn _read_from(uni: &TupUniAttribute) -> Result<Self, DecodeErr>
+    where
+        Self: Sized;
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TupUniAttribute {
     version: ProtocolVersion,
     simple_map: SimpleTupMap,
     complex_map: ComplexTupMap,
+    dialect: ClassNameDialect,
+    cache: TupCache,
+}
+
+type CachedValue = Arc<dyn Any + Send + Sync>;
+
+type CachedValues = HashMap<String, HashMap<TypeId, CachedValue>>;
+
+// read_cached 的解码结果，以 key 与 rust 类型为索引 (i32 与 u16 的类型名同为 int32)
+// 不影响 TupUniAttribute 的比较，clone 时只保留是否开启
+#[derive(Default)]
+struct TupCache {
+    enabled: bool,
+    values: Mutex<CachedValues>,
+}
+
+impl TupCache {
+    fn values(&self) -> MutexGuard<'_, CachedValues> {
+        match self.values.lock() {
+            Ok(values) => values,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn invalidate(&mut self, name: &str) {
+        self.values().remove(name);
+    }
+
+    fn clear(&mut self) {
+        self.values().clear();
+    }
+}
+
+impl Clone for TupCache {
+    fn clone(&self) -> Self {
+        TupCache {
+            enabled: self.enabled,
+            values: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl fmt::Debug for TupCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TupCache")
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+impl PartialEq for TupCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for TupCache {}
+
+impl PartialOrd for TupCache {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TupCache {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
 }
 // for SimpleTup protocol version
 impl TupUniAttribute {
@@ -35,6 +120,8 @@ impl TupUniAttribute {
             version,
             simple_map: BTreeMap::new(),
             complex_map: BTreeMap::new(),
+            dialect: ClassNameDialect::rust(),
+            cache: TupCache::default(),
         }
     }
 
@@ -44,16 +131,65 @@ impl TupUniAttribute {
                 version,
                 simple_map: TarsDecoder::individual_decode(buf)?,
                 complex_map: BTreeMap::new(),
+                dialect: ClassNameDialect::rust(),
+                cache: TupCache::default(),
             }),
             ProtocolVersion::TupComplex => Ok(TupUniAttribute {
                 version,
                 simple_map: BTreeMap::new(),
                 complex_map: TarsDecoder::individual_decode(buf)?,
+                dialect: ClassNameDialect::rust(),
+                cache: TupCache::default(),
             }),
             _ => Err(DecodeErr::UnsupportTupVersionErr),
         }
     }
 
+    // 根据第一个 value 的类型区分 map<string, bytes> 与 map<string, map<string, bytes>>
+    // 空 map 两种版本的编码相同，按 TupSimple 处理
+    pub fn from_bytes_auto(buf: &Bytes) -> Result<(Self, ProtocolVersion), DecodeErr> {
+        let version = Self::detect_version(buf)?;
+        Ok((Self::from_bytes(buf, version)?, version))
+    }
+
+    fn detect_version(buf: &[u8]) -> Result<ProtocolVersion, DecodeErr> {
+        let mut reader = TarsReader::new(buf);
+        let mut next = || match reader.next() {
+            Some(event) => event,
+            None => Err(DecodeErr::NoEnoughDataErr),
+        };
+        match next()? {
+            TarsEvent::Field {
+                tag: 0,
+                tars_type: EnMaps,
+            } => {}
+            TarsEvent::Field { tag: 0, .. } => return Err(DecodeErr::MisMatchTarsTypeErr),
+            _ => return Err(DecodeErr::TarsTagNotFoundErr),
+        }
+        match next()? {
+            TarsEvent::MapStart(0) => return Ok(ProtocolVersion::TupSimple),
+            TarsEvent::MapStart(_) => {}
+            _ => return Err(DecodeErr::MisMatchTarsTypeErr),
+        }
+        // key
+        next()?;
+        next()?;
+        match next()? {
+            TarsEvent::Field {
+                tars_type: EnSimplelist,
+                ..
+            } => Ok(ProtocolVersion::TupSimple),
+            TarsEvent::Field {
+                tars_type: EnMaps, ..
+            } => Ok(ProtocolVersion::TupComplex),
+            _ => Err(DecodeErr::MisMatchTarsTypeErr),
+        }
+    }
+
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
     pub fn to_bytes(&self) -> Result<Bytes, EncodeErr> {
         match self.version {
             ProtocolVersion::TupSimple => TarsEncoder::individual_encode(&self.simple_map),
@@ -62,7 +198,7 @@ impl TupUniAttribute {
         }
     }
 
-    pub fn read<T>(&self, name: &String, is_require: bool, default_value: T) -> Result<T, DecodeErr>
+    pub fn read<T>(&self, name: &str, is_require: bool, default_value: T) -> Result<T, DecodeErr>
     where
         T: DecodeTars + ClassName,
     {
@@ -75,7 +211,7 @@ impl TupUniAttribute {
                 )?),
             },
             ProtocolVersion::TupComplex => match self.complex_map.get(name) {
-                Some(item) => match item.get(&T::_class_name()) {
+                Some(item) => match Self::find_by_class_name(item, &T::_class_name_cow()) {
                     Some(b) => Ok(TarsDecoder::individual_decode(b)?),
                     None => Ok(Self::return_error_if_required_not_found(
                         is_require,
@@ -91,20 +227,102 @@ impl TupUniAttribute {
         }
     }
 
-    pub fn write<T>(&mut self, name: &String, value: &T) -> Result<(), EncodeErr>
+    // key (或 TupComplex 下该类型) 不存在时返回 None
+    pub fn try_read<T>(&self, name: &str) -> Result<Option<T>, DecodeErr>
+    where
+        T: DecodeTars + ClassName,
+    {
+        let b = match self.version {
+            ProtocolVersion::TupSimple => self.simple_map.get(name),
+            ProtocolVersion::TupComplex => self
+                .complex_map
+                .get(name)
+                .and_then(|item| Self::find_by_class_name(item, &T::_class_name_cow())),
+            _ => return Err(DecodeErr::UnsupportTupVersionErr),
+        };
+        match b {
+            Some(b) => Ok(Some(TarsDecoder::individual_decode(b)?)),
+            None => Ok(None),
+        }
+    }
+
+    // 按字段名写入 struct 的全部字段
+    pub fn write_fields<T>(&mut self, ele: &T) -> Result<(), EncodeErr>
+    where
+        T: TupAttribute + ?Sized,
+    {
+        ele._write_to(self)
+    }
+
+    pub fn read_fields<T>(&self) -> Result<T, DecodeErr>
     where
-        T: EncodeTars + ClassName,
+        T: TupAttribute,
     {
+        T::_read_from(self)
+    }
+
+    // 开启后 read_cached 只在第一次读取时解码，write / remove / merge / clear 会使对应的缓存失效
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.cache.enabled = enabled;
+        self
+    }
+
+    // 与 try_read 相同，返回共享的解码结果；未开启缓存时每次都解码
+    pub fn read_cached<T>(&self, name: &str) -> Result<Option<Arc<T>>, DecodeErr>
+    where
+        T: DecodeTars + ClassName + Send + Sync + 'static,
+    {
+        if !self.cache.enabled {
+            return Ok(self.try_read::<T>(name)?.map(Arc::new));
+        }
+        let type_id = TypeId::of::<T>();
+        let cached = self
+            .cache
+            .values()
+            .get(name)
+            .and_then(|item| item.get(&type_id).cloned());
+        if let Some(Ok(value)) = cached.map(|value| value.downcast::<T>()) {
+            return Ok(Some(value));
+        }
+        // 解码时不持有锁，避免阻塞同一 attribute 上的其他读取
+        let value = match self.try_read::<T>(name)? {
+            Some(value) => Arc::new(value),
+            None => return Ok(None),
+        };
+        let mut values = self.cache.values();
+        if !values.contains_key(name) {
+            values.insert(name.to_string(), HashMap::new());
+        }
+        let cached = match values.get_mut(name) {
+            // 多个线程同时解码时保留先写入的结果
+            Some(item) => item.entry(type_id).or_insert_with(|| value.clone()).clone(),
+            None => return Ok(Some(value)),
+        };
+        Ok(Some(cached.downcast::<T>().unwrap_or(value)))
+    }
+
+    pub fn write<T>(&mut self, name: &str, value: &T) -> Result<(), EncodeErr>
+    where
+        T: EncodeTars + ClassName + ?Sized,
+    {
+        self.cache.invalidate(name);
         match self.version {
             ProtocolVersion::TupSimple => {
                 self.simple_map
-                    .insert(name.clone(), TarsEncoder::individual_encode(value)?);
+                    .insert(name.to_string(), TarsEncoder::individual_encode(value)?);
                 Ok(())
             }
             ProtocolVersion::TupComplex => {
-                let mut item: BTreeMap<String, Bytes> = BTreeMap::new();
-                item.insert(T::_class_name(), TarsEncoder::individual_encode(value)?);
-                self.complex_map.insert(name.clone(), item);
+                // 同一个 key 下不同类型的值共存，同类型 (包括其他语言的写法) 覆盖
+                let value = TarsEncoder::individual_encode(value)?;
+                let class_name = T::_class_name_cow();
+                let class_name = self.dialect.translate(&class_name);
+                if !self.complex_map.contains_key(name) {
+                    self.complex_map.insert(name.to_string(), BTreeMap::new());
+                }
+                if let Some(item) = self.complex_map.get_mut(name) {
+                    Self::insert_by_class_name(item, &class_name, value);
+                }
                 Ok(())
             }
             _ => Err(EncodeErr::UnsupportTupVersionErr),
@@ -112,9 +330,263 @@ impl TupUniAttribute {
     }
 }
 
+// TupSimple 转为 TupComplex 时各 key 的类型名
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TupClassRegistry {
+    class_names: BTreeMap<String, String>,
+}
+
+impl TupClassRegistry {
+    pub fn new() -> Self {
+        TupClassRegistry::default()
+    }
+
+    pub fn with_type<T>(self, name: &str) -> Self
+    where
+        T: ClassName + ?Sized,
+    {
+        self.with_class_name(name, &T::_class_name())
+    }
+
+    pub fn with_class_name(mut self, name: &str, class_name: &str) -> Self {
+        self.class_names.insert(name.to_string(), class_name.to_string());
+        self
+    }
+
+    pub fn class_name(&self, name: &str) -> Option<&str> {
+        self.class_names.get(name).map(|s| s.as_str())
+    }
+}
+
+// TupSimple 下 class_name 为 None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TupEntry<'a> {
+    pub name: &'a str,
+    pub class_name: Option<&'a str>,
+    pub value: &'a Bytes,
+}
+
+// key 管理，TupSimple 与 TupComplex 行为一致
+impl TupUniAttribute {
+    pub fn keys(&self) -> Vec<&str> {
+        match self.version {
+            ProtocolVersion::TupComplex => self.complex_map.keys().map(|k| k.as_str()).collect(),
+            _ => self.simple_map.keys().map(|k| k.as_str()).collect(),
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        match self.version {
+            ProtocolVersion::TupComplex => self.complex_map.contains_key(name),
+            _ => self.simple_map.contains_key(name),
+        }
+    }
+
+    // 删除 key 下的全部值，返回 key 是否存在
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.cache.invalidate(name);
+        match self.version {
+            ProtocolVersion::TupComplex => self.complex_map.remove(name).is_some(),
+            _ => self.simple_map.remove(name).is_some(),
+        }
+    }
+
+    // key 的个数
+    pub fn len(&self) -> usize {
+        match self.version {
+            ProtocolVersion::TupComplex => self.complex_map.len(),
+            _ => self.simple_map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 按 key 与类型名的字典序遍历未解码的值
+    pub fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = TupEntry<'a>> + 'a> {
+        match self.version {
+            ProtocolVersion::TupComplex => Box::new(self.complex_map.iter().flat_map(|(name, item)| {
+                item.iter().map(move |(class_name, value)| TupEntry {
+                    name,
+                    class_name: Some(class_name),
+                    value,
+                })
+            })),
+            _ => Box::new(self.simple_map.iter().map(|(name, value)| TupEntry {
+                name,
+                class_name: None,
+                value,
+            })),
+        }
+    }
+
+    // other 中的值覆盖同名 (TupComplex 下为同名同类型) 的值，版本不同时返回错误
+    pub fn merge(&mut self, other: &TupUniAttribute) -> Result<(), EncodeErr> {
+        if self.version != other.version {
+            return Err(EncodeErr::UnsupportTupVersionErr);
+        }
+        match self.version {
+            ProtocolVersion::TupComplex => for (name, other_item) in other.complex_map.iter() {
+                self.cache.invalidate(name);
+                let item = self.complex_map.entry(name.clone()).or_default();
+                for (class_name, value) in other_item.iter() {
+                    Self::insert_by_class_name(item, class_name, value.clone());
+                }
+            },
+            _ => for (name, value) in other.simple_map.iter() {
+                self.cache.invalidate(name);
+                self.simple_map.insert(name.clone(), value.clone());
+            },
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.simple_map.clear();
+        self.complex_map.clear();
+        self.cache.clear();
+    }
+
+    // TupSimple -> TupComplex: 类型名取自 registry 并按 dialect 转换，缺少时返回 TypeNotFoundErr
+    // TupComplex -> TupSimple: 去掉类型一层，同一个 key 有多个类型时返回 TupAmbiguousTypeErr
+    // 值的字节不重新编码
+    pub fn convert_to(
+        &self,
+        version: ProtocolVersion,
+        registry: &TupClassRegistry,
+    ) -> Result<TupUniAttribute, DecodeErr> {
+        let mut converted =
+            TupUniAttribute::new(version).with_class_name_dialect(self.dialect.clone());
+        match (self.version, version) {
+            (from, to) if from == to => return Ok(self.clone()),
+            (ProtocolVersion::TupSimple, ProtocolVersion::TupComplex) => {
+                for (name, value) in self.simple_map.iter() {
+                    let class_name = match registry.class_name(name) {
+                        Some(class_name) => self.dialect.translate(class_name).into_owned(),
+                        None => {
+                            return Err(DecodeErr::TypeNotFoundErr(format!(
+                                "tup: no class name for key {}",
+                                name
+                            )))
+                        }
+                    };
+                    let mut item = BTreeMap::new();
+                    item.insert(class_name, value.clone());
+                    converted.complex_map.insert(name.clone(), item);
+                }
+            }
+            (ProtocolVersion::TupComplex, ProtocolVersion::TupSimple) => {
+                for (name, item) in self.complex_map.iter() {
+                    if item.len() > 1 {
+                        return Err(DecodeErr::TupAmbiguousTypeErr(format!(
+                            "tup: key {} holds {} types",
+                            name,
+                            item.len()
+                        )));
+                    }
+                    // 没有值的 key 丢弃
+                    if let Some(value) = item.values().next() {
+                        converted.simple_map.insert(name.clone(), value.clone());
+                    }
+                }
+            }
+            _ => return Err(DecodeErr::UnsupportTupVersionErr),
+        }
+        Ok(converted)
+    }
+}
+
+// for TupComplex protocol version
+impl TupUniAttribute {
+    // 写入时使用的类型名写法，默认与 ClassName 一致
+    pub fn with_class_name_dialect(mut self, dialect: ClassNameDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    // 先按类型名查找，找不到时按其他语言的等价写法查找
+    fn find_by_class_name<'a>(
+        item: &'a BTreeMap<String, Bytes>,
+        class_name: &str,
+    ) -> Option<&'a Bytes> {
+        item.get(class_name).or_else(|| {
+            let normalized = normalize_class_name(class_name);
+            item.iter()
+                .find(|(k, _)| normalize_class_name(k) == normalized)
+                .map(|(_, v)| v)
+        })
+    }
+
+    // 同名类型直接覆盖，否则先删除等价写法的旧值
+    fn insert_by_class_name(item: &mut BTreeMap<String, Bytes>, class_name: &str, value: Bytes) {
+        if let Some(old) = item.get_mut(class_name) {
+            *old = value;
+            return;
+        }
+        let normalized = normalize_class_name(class_name);
+        item.retain(|k, _| normalize_class_name(k) != normalized);
+        item.insert(class_name.to_string(), value);
+    }
+
+    // key 下已有值的类型名，按字典序；key 不存在时为空
+    pub fn class_names(&self, name: &str) -> Result<Vec<String>, DecodeErr> {
+        match self.version {
+            ProtocolVersion::TupComplex => Ok(self
+                .complex_map
+                .get(name)
+                .map(|item| item.keys().cloned().collect())
+                .unwrap_or_default()),
+            _ => Err(DecodeErr::UnsupportTupVersionErr),
+        }
+    }
+
+    // 按指定的类型名读取，不要求与 T::_class_name() 一致，同样接受等价写法
+    pub fn read_by_class_name<T>(
+        &self,
+        name: &str,
+        class_name: &str,
+        is_require: bool,
+        default_value: T,
+    ) -> Result<T, DecodeErr>
+    where
+        T: DecodeTars,
+    {
+        match self.version {
+            ProtocolVersion::TupComplex => match self
+                .complex_map
+                .get(name)
+                .and_then(|item| Self::find_by_class_name(item, class_name))
+            {
+                Some(b) => Ok(TarsDecoder::individual_decode(b)?),
+                None => Self::return_error_if_required_not_found(is_require, default_value),
+            },
+            _ => Err(DecodeErr::UnsupportTupVersionErr),
+        }
+    }
+}
+
+// 与 C++ 中对内部 map 调用 display 的输出一致，value 为编码后的 vector<char>
+impl TarsDisplay for TupUniAttribute {
+    fn _display(&self, ds: &mut TarsDisplayer, field_name: Option<&str>) {
+        match self.version {
+            ProtocolVersion::TupComplex => ds.display(&self.complex_map, field_name),
+            _ => ds.display(&self.simple_map, field_name),
+        };
+    }
+
+    fn _display_simple(&self, ds: &mut TarsDisplayer, sep: bool) {
+        match self.version {
+            ProtocolVersion::TupComplex => ds.display_simple(&self.complex_map, sep),
+            _ => ds.display_simple(&self.simple_map, sep),
+        };
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::borrow::Cow;
     use tars_encoder::*;
 
     #[test]
@@ -736,4 +1208,381 @@ mod tests {
         let de_fake_value = uni.read(&fake_key, false, 0).unwrap();
         assert_eq!(de_fake_value, 0);
     }
+
+    #[test]
+    fn test_display_tup() {
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupSimple);
+        uni.write("a", &1i32).unwrap();
+        let mut ds = TarsDisplayer::new();
+        ds.display(&uni, Some("uni"));
+        assert_eq!(
+            ds.into_string(),
+            "uni: 1, {\n\t(\n\t\ta\n\t\t2, 0001\n\t)\n}\n"
+        );
+
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write("a", &1i32).unwrap();
+        let mut ds = TarsDisplayer::new();
+        ds.display_simple(&uni, false);
+        assert_eq!(ds.into_string(), "1{a|1{int32|0001}}");
+    }
+
+    #[test]
+    fn test_from_bytes_auto() {
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupSimple);
+        uni.write("a", &1i32).unwrap();
+        let (de, version) = TupUniAttribute::from_bytes_auto(&uni.to_bytes().unwrap()).unwrap();
+        assert_eq!(version, ProtocolVersion::TupSimple);
+        assert_eq!(de, uni);
+
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write("a", &1i32).unwrap();
+        let (de, version) = TupUniAttribute::from_bytes_auto(&uni.to_bytes().unwrap()).unwrap();
+        assert_eq!(version, ProtocolVersion::TupComplex);
+        assert_eq!(de.read("a", true, 0i32), Ok(1));
+
+        let uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        let (_, version) = TupUniAttribute::from_bytes_auto(&uni.to_bytes().unwrap()).unwrap();
+        assert_eq!(version, ProtocolVersion::TupSimple);
+
+        assert_eq!(
+            TupUniAttribute::from_bytes_auto(&TarsEncoder::individual_encode(&1i32).unwrap()),
+            Err(DecodeErr::MisMatchTarsTypeErr)
+        );
+        let mut map = BTreeMap::new();
+        map.insert("a".to_string(), 1i32);
+        assert_eq!(
+            TupUniAttribute::from_bytes_auto(&TarsEncoder::individual_encode(&map).unwrap()),
+            Err(DecodeErr::MisMatchTarsTypeErr)
+        );
+        assert_eq!(
+            TupUniAttribute::from_bytes_auto(&Bytes::new()),
+            Err(DecodeErr::NoEnoughDataErr)
+        );
+    }
+
+    #[test]
+    fn test_complex_tup_multi_type() {
+        let key = "k";
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write(key, &1i32).unwrap();
+        uni.write(key, &"foo".to_string()).unwrap();
+        uni.write(key, &2i32).unwrap();
+
+        let de = TupUniAttribute::from_bytes(&uni.to_bytes().unwrap(), ProtocolVersion::TupComplex)
+            .unwrap();
+        assert_eq!(
+            de.class_names(key),
+            Ok(vec!["int32".to_string(), "string".to_string()])
+        );
+        assert_eq!(de.class_names("none"), Ok(vec![]));
+        assert_eq!(de.read(key, true, 0i32), Ok(2));
+        assert_eq!(de.read(key, true, String::new()), Ok("foo".to_string()));
+        assert_eq!(
+            de.read_by_class_name(key, "string", true, String::new()),
+            Ok("foo".to_string())
+        );
+        // int32 以最小宽度编码，可按 int64 读出
+        assert_eq!(de.read_by_class_name(key, "int32", true, 0i64), Ok(2));
+        assert_eq!(
+            de.read_by_class_name(key, "bool", true, false),
+            Err(DecodeErr::TupKeyNotFoundErr)
+        );
+        assert_eq!(de.read_by_class_name(key, "bool", false, true), Ok(true));
+
+        let simple = TupUniAttribute::new(ProtocolVersion::TupSimple);
+        assert_eq!(
+            simple.class_names(key),
+            Err(DecodeErr::UnsupportTupVersionErr)
+        );
+        assert_eq!(
+            simple.read_by_class_name(key, "int32", false, 0i32),
+            Err(DecodeErr::UnsupportTupVersionErr)
+        );
+    }
+
+    // 按 C++ Class<T>::name() 的类型名写法手工构造的 TupComplex 数据，并非抓取自其他语言的实现
+    // b 为 vector<char> "hi"，v 为 vector<int32> {1, 2}
+    const CPP_STYLE_VECTOR: &[u8] =
+        b"\x08\x00\x02\x06\x01b\x18\x00\x01\x06\x0alist<char>\x1d\x00\x00\x06\
+          \x0d\x00\x00\x02hi\x06\x01v\x18\x00\x01\x06\x0blist<int32>\x1d\x00\x00\x07\
+          \x09\x00\x02\x00\x01\x00\x02";
+
+    // 按 Tars-Java BasicClassTypeUtil 的类型名写法手工构造，同样并非抓取自 Java 实现
+    // m 为 Map<String, Integer> {"a": 1}，l 为 List<Long> {5}，d 为 byte[] "x"
+    const JAVA_STYLE_VECTOR: &[u8] =
+        b"\x08\x00\x03\x06\x01d\x18\x00\x01\x06\x0alist<char>\x1d\x00\x00\x05\
+          \x0d\x00\x00\x01x\x06\x01l\x18\x00\x01\x06\x0blist<int64>\x1d\x00\x00\x05\x09\x00\x01\x00\x05\
+          \x06\x01m\x18\x00\x01\x06\x11map<string,int32>\x1d\x00\x00\x08\x08\x00\x01\x06\x01a\x10\x01";
+
+    // 按 @tars/stream 的 _classname 手工构造，同样并非抓取自 Node 实现
+    // s 为 TarsStream.List(TarsStream.String) ["a"]，u 为 TarsStream.UInt8 200
+    const NODE_STYLE_VECTOR: &[u8] =
+        b"\x08\x00\x02\x06\x01s\x18\x00\x01\x06\x0clist<string>\x1d\x00\x00\x06\
+          \x09\x00\x01\x06\x01a\x06\x01u\x18\x00\x01\x06\x05short\x1d\x00\x00\x03\x01\x00\xc8";
+
+    #[test]
+    fn test_class_name_interop_java_node() {
+        let buf = Bytes::from(JAVA_STYLE_VECTOR);
+        let uni = TupUniAttribute::from_bytes(&buf, ProtocolVersion::TupComplex).unwrap();
+        let mut m = BTreeMap::new();
+        m.insert("a".to_string(), 1i32);
+        assert_eq!(uni.read("m", true, BTreeMap::new()), Ok(m.clone()));
+        assert_eq!(uni.read("l", true, vec![0i64]), Ok(vec![5]));
+        assert_eq!(uni.read("d", true, Bytes::new()), Ok(Bytes::from("x")));
+        let mut java = TupUniAttribute::new(ProtocolVersion::TupComplex)
+            .with_class_name_dialect(ClassNameDialect::java());
+        java.write("m", &m).unwrap();
+        java.write("l", &vec![5i64]).unwrap();
+        java.write("d", &Bytes::from("x")).unwrap();
+        assert_eq!(java.to_bytes(), Ok(buf));
+
+        let buf = Bytes::from(NODE_STYLE_VECTOR);
+        let uni = TupUniAttribute::from_bytes(&buf, ProtocolVersion::TupComplex).unwrap();
+        assert_eq!(uni.read("s", true, vec![]), Ok(vec!["a".to_string()]));
+        assert_eq!(uni.read("u", true, 0u8), Ok(200));
+        let mut node = TupUniAttribute::new(ProtocolVersion::TupComplex)
+            .with_class_name_dialect(ClassNameDialect::node());
+        node.write("s", &vec!["a".to_string()]).unwrap();
+        node.write("u", &200u8).unwrap();
+        assert_eq!(node.to_bytes(), Ok(buf));
+    }
+
+    #[test]
+    fn test_class_name_interop() {
+        let buf = Bytes::from(CPP_STYLE_VECTOR);
+        let uni = TupUniAttribute::from_bytes(&buf, ProtocolVersion::TupComplex).unwrap();
+        let b = "b";
+        let v = "v";
+        assert_eq!(uni.read(b, true, Bytes::new()), Ok(Bytes::from("hi")));
+        assert_eq!(uni.read(b, true, vec![0i8]), Ok(vec![b'h' as i8, b'i' as i8]));
+        assert_eq!(uni.read(v, true, vec![0i32]), Ok(vec![1, 2]));
+        assert_eq!(uni.read_by_class_name(v, "vector<int32>", true, vec![0i32]), Ok(vec![1, 2]));
+        // list<short> 与 list<int32> 不等价
+        assert_eq!(uni.read(v, false, vec![0i16]), Ok(vec![0]));
+
+        let mut cpp = TupUniAttribute::new(ProtocolVersion::TupComplex)
+            .with_class_name_dialect(ClassNameDialect::cpp());
+        cpp.write(b, &Bytes::from("hi")).unwrap();
+        cpp.write(v, &vec![1i32, 2]).unwrap();
+        assert_eq!(cpp.to_bytes(), Ok(buf.clone()));
+
+        // 以 rust 写法覆盖时替换等价写法的旧值
+        let mut uni = uni;
+        uni.write(v, &vec![3i32]).unwrap();
+        assert_eq!(uni.class_names(v), Ok(vec!["List<int32>".to_string()]));
+        assert_eq!(uni.read(v, true, vec![0i32]), Ok(vec![3]));
+    }
+
+    #[test]
+    fn test_key_management() {
+        for &version in [ProtocolVersion::TupSimple, ProtocolVersion::TupComplex].iter() {
+            let mut uni = TupUniAttribute::new(version);
+            assert!(uni.is_empty());
+            uni.write("a", &1i32).unwrap();
+            uni.write("b", &"foo".to_string()).unwrap();
+            uni.write("c", &true).unwrap();
+            assert_eq!(uni.len(), 3);
+            assert_eq!(uni.keys(), vec!["a", "b", "c"]);
+            assert!(uni.contains("b"));
+            assert!(uni.remove("b"));
+            assert!(!uni.remove("b"));
+            assert!(!uni.contains("b"));
+
+            let entries: Vec<(&str, Option<&str>)> =
+                uni.iter().map(|e| (e.name, e.class_name)).collect();
+            match version {
+                ProtocolVersion::TupSimple => {
+                    assert_eq!(entries, vec![("a", None), ("c", None)])
+                }
+                _ => assert_eq!(
+                    entries,
+                    vec![("a", Some("int32")), ("c", Some("bool"))]
+                ),
+            }
+            let value = uni.iter().next().unwrap().value.clone();
+            assert_eq!(TarsDecoder::individual_decode(&value), Ok(1i32));
+
+            let mut other = TupUniAttribute::new(version);
+            other.write("a", &2i32).unwrap();
+            other.write("d", &3i64).unwrap();
+            uni.merge(&other).unwrap();
+            assert_eq!(uni.keys(), vec!["a", "c", "d"]);
+            assert_eq!(uni.read("a", true, 0i32), Ok(2));
+            assert_eq!(uni.read("c", true, false), Ok(true));
+
+            uni.clear();
+            assert!(uni.is_empty());
+            assert_eq!(uni.iter().count(), 0);
+        }
+
+        // TupComplex 下同名不同类型的值合并后共存
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write("a", &1i32).unwrap();
+        let mut other = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        other.write("a", &"foo".to_string()).unwrap();
+        uni.merge(&other).unwrap();
+        assert_eq!(uni.len(), 1);
+        assert_eq!(uni.iter().count(), 2);
+
+        assert_eq!(
+            uni.merge(&TupUniAttribute::new(ProtocolVersion::TupSimple)),
+            Err(EncodeErr::UnsupportTupVersionErr)
+        );
+    }
+
+    #[test]
+    fn test_convert_to() {
+        let mut simple = TupUniAttribute::new(ProtocolVersion::TupSimple);
+        simple.write("a", &1i32).unwrap();
+        simple.write("b", &vec![1i32]).unwrap();
+
+        let registry = TupClassRegistry::new()
+            .with_type::<i32>("a")
+            .with_class_name("b", "list<int32>");
+        let complex = simple
+            .convert_to(ProtocolVersion::TupComplex, &registry)
+            .unwrap();
+        assert_eq!(complex.version(), ProtocolVersion::TupComplex);
+        assert_eq!(complex.class_names("a"), Ok(vec!["int32".to_string()]));
+        assert_eq!(complex.read("a", true, 0i32), Ok(1));
+        assert_eq!(complex.read("b", true, vec![0i32]), Ok(vec![1]));
+
+        assert_eq!(
+            complex.convert_to(ProtocolVersion::TupSimple, &TupClassRegistry::new()),
+            Ok(simple.clone())
+        );
+        assert_eq!(
+            simple.convert_to(ProtocolVersion::TupSimple, &TupClassRegistry::new()),
+            Ok(simple.clone())
+        );
+        assert_eq!(
+            simple.convert_to(
+                ProtocolVersion::TupComplex,
+                &TupClassRegistry::new().with_type::<i32>("a")
+            ),
+            Err(DecodeErr::TypeNotFoundErr(
+                "tup: no class name for key b".to_string()
+            ))
+        );
+        assert_eq!(
+            simple.convert_to(ProtocolVersion::Tars, &registry),
+            Err(DecodeErr::UnsupportTupVersionErr)
+        );
+
+        let mut complex = complex;
+        complex.write("a", &"foo".to_string()).unwrap();
+        assert_eq!(
+            complex.convert_to(ProtocolVersion::TupSimple, &registry),
+            Err(DecodeErr::TupAmbiguousTypeErr(
+                "tup: key a holds 2 types".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_try_read() {
+        for &version in [ProtocolVersion::TupSimple, ProtocolVersion::TupComplex].iter() {
+            let mut uni = TupUniAttribute::new(version);
+            uni.write("a", &1i32).unwrap();
+            assert_eq!(uni.try_read::<i32>("a"), Ok(Some(1)));
+            assert_eq!(uni.try_read::<i32>("b"), Ok(None));
+        }
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write("a", &1i32).unwrap();
+        assert_eq!(uni.try_read::<String>("a"), Ok(None));
+        assert_eq!(
+            TupUniAttribute::new(ProtocolVersion::Tars).try_read::<i32>("a"),
+            Err(DecodeErr::UnsupportTupVersionErr)
+        );
+    }
+
+    #[test]
+    fn test_borrowed_value() {
+        // 非 'static 的类型同样可以读写
+        let s = String::from("foo");
+        let v = vec![1i32, 2];
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupComplex);
+        uni.write("s", &Cow::Borrowed(s.as_str())).unwrap();
+        uni.write("v", &&v).unwrap();
+        assert_eq!(uni.class_names("s"), Ok(vec!["string".to_string()]));
+        assert_eq!(uni.class_names("v"), Ok(vec!["List<int32>".to_string()]));
+        assert_eq!(
+            uni.read("s", true, Cow::Borrowed(s.as_str())),
+            Ok(Cow::Owned(s.clone()))
+        );
+        assert_eq!(uni.try_read::<Vec<i32>>("v"), Ok(Some(v.clone())));
+    }
+
+    #[test]
+    fn test_insert_by_class_name_exact_hit() {
+        let mut item = BTreeMap::new();
+        item.insert("list<char>".to_string(), Bytes::from("a"));
+        item.insert("vector<char>".to_string(), Bytes::from("b"));
+
+        let insert = TupUniAttribute::insert_by_class_name;
+        // 精确命中时直接覆盖，不检查等价写法
+        insert(&mut item, "list<char>", Bytes::from("c"));
+        assert_eq!(item.len(), 2);
+        assert_eq!(item.get("list<char>"), Some(&Bytes::from("c")));
+        assert_eq!(item.get("vector<char>"), Some(&Bytes::from("b")));
+
+        // 未命中时删除等价写法的旧值
+        insert(&mut item, "List<char>", Bytes::from("d"));
+        assert_eq!(item.len(), 1);
+        assert_eq!(item.get("List<char>"), Some(&Bytes::from("d")));
+    }
+
+    #[test]
+    fn test_read_cached() {
+        for &version in [ProtocolVersion::TupSimple, ProtocolVersion::TupComplex].iter() {
+            let mut uni = TupUniAttribute::new(version).with_cache(true);
+            uni.write("a", &vec![1i32, 2]).unwrap();
+
+            let first = uni.read_cached::<Vec<i32>>("a").unwrap().unwrap();
+            assert_eq!(*first, vec![1, 2]);
+            let second = uni.read_cached::<Vec<i32>>("a").unwrap().unwrap();
+            assert!(Arc::ptr_eq(&first, &second));
+            assert_eq!(uni.read_cached::<Vec<i32>>("b"), Ok(None));
+
+            // 覆盖后重新解码
+            uni.write("a", &vec![3i32]).unwrap();
+            assert_eq!(
+                uni.read_cached::<Vec<i32>>("a"),
+                Ok(Some(Arc::new(vec![3])))
+            );
+            let mut other = TupUniAttribute::new(version);
+            other.write("a", &vec![4i32]).unwrap();
+            uni.merge(&other).unwrap();
+            assert_eq!(
+                uni.read_cached::<Vec<i32>>("a"),
+                Ok(Some(Arc::new(vec![4])))
+            );
+            uni.remove("a");
+            assert_eq!(uni.read_cached::<Vec<i32>>("a"), Ok(None));
+
+            // clone 不共享缓存
+            uni.write("a", &1i32).unwrap();
+            let first = uni.read_cached::<i32>("a").unwrap().unwrap();
+            let cloned = uni.clone();
+            let second = cloned.read_cached::<i32>("a").unwrap().unwrap();
+            assert!(!Arc::ptr_eq(&first, &second));
+            assert_eq!(cloned, uni);
+
+            // 类型名相同的不同类型分别缓存，互不覆盖
+            let short = uni.read_cached::<u16>("a").unwrap().unwrap();
+            assert_eq!(*short, 1);
+            let again = uni.read_cached::<i32>("a").unwrap().unwrap();
+            assert!(Arc::ptr_eq(&first, &again));
+            assert!(Arc::ptr_eq(&short, &uni.read_cached::<u16>("a").unwrap().unwrap()));
+            uni.clear();
+            assert_eq!(uni.read_cached::<i32>("a"), Ok(None));
+        }
+
+        let mut uni = TupUniAttribute::new(ProtocolVersion::TupSimple);
+        uni.write("a", &1i32).unwrap();
+        let first = uni.read_cached::<i32>("a").unwrap().unwrap();
+        let second = uni.read_cached::<i32>("a").unwrap().unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+    }
 }
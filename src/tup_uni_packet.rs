@@ -0,0 +1,295 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut, IntoBuf};
+use errors::{DecodeErr, EncodeErr};
+use std::collections::BTreeMap;
+
+use tars_decoder::{DecodeTars, TarsDecodeNormalTrait, TarsDecoder};
+use tars_encoder::{EncodeTars, TarsEncoder, TarsEncoderNormalTrait};
+use tars_trait::{ClassName, StructFromTars, StructToTars};
+use tars_type::ProtocolVersion;
+use tup_uni_attribute::TupUniAttribute;
+
+// 与 RequestPacket.tars 一致，tag 从 1 开始
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RequestPacket {
+    pub version: i16,
+    pub packet_type: i8,
+    pub message_type: i32,
+    pub request_id: i32,
+    pub servant_name: String,
+    pub func_name: String,
+    pub buffer: Bytes,
+    pub timeout: i32,
+    pub context: BTreeMap<String, String>,
+    pub status: BTreeMap<String, String>,
+}
+
+impl StructToTars for RequestPacket {
+    fn _encode_to(&self, encoder: &mut TarsEncoder) -> Result<(), EncodeErr> {
+        encoder.write_int16(1, self.version)?;
+        encoder.write_int8(2, self.packet_type)?;
+        encoder.write_int32(3, self.message_type)?;
+        encoder.write_int32(4, self.request_id)?;
+        encoder.write_string(5, &self.servant_name)?;
+        encoder.write_string(6, &self.func_name)?;
+        encoder.write_bytes(7, &self.buffer)?;
+        encoder.write_int32(8, self.timeout)?;
+        encoder.write_map(9, &self.context)?;
+        encoder.write_map(10, &self.status)?;
+        Ok(())
+    }
+}
+
+impl StructFromTars for RequestPacket {
+    fn _decode_from(decoder: &mut TarsDecoder) -> Result<Self, DecodeErr> {
+        let version = decoder.read_int16(1, true, 0)?;
+        let packet_type = decoder.read_int8(2, true, 0)?;
+        let message_type = decoder.read_int32(3, true, 0)?;
+        let request_id = decoder.read_int32(4, true, 0)?;
+        let servant_name = decoder.read_string(5, true, String::new())?;
+        let func_name = decoder.read_string(6, true, String::new())?;
+        let buffer = decoder.read_bytes(7, true, Bytes::new())?;
+        let timeout = decoder.read_int32(8, true, 0)?;
+        let context = decoder.read_map(9, true, BTreeMap::new())?;
+        let status = decoder.read_map(10, true, BTreeMap::new())?;
+        Ok(RequestPacket {
+            version,
+            packet_type,
+            message_type,
+            request_id,
+            servant_name,
+            func_name,
+            buffer,
+            timeout,
+            context,
+            status,
+        })
+    }
+}
+
+impl DecodeTars for RequestPacket {
+    fn _decode(decoder: &mut TarsDecoder, tag: u8) -> Result<Self, DecodeErr> {
+        decoder.read_struct(tag, true, RequestPacket::default())
+    }
+}
+
+impl EncodeTars for RequestPacket {
+    fn _encode(&self, encoder: &mut TarsEncoder, tag: u8) -> Result<(), EncodeErr> {
+        encoder.write_struct(tag, self)
+    }
+}
+
+impl ClassName for RequestPacket {
+    fn _class_name() -> String {
+        String::from("tars.RequestPacket")
+    }
+}
+
+// 完整的 Tup 请求/响应包，sBuffer 由 TupUniAttribute 编码
+// 编码结果带 4 字节大端长度前缀，长度包含前缀本身
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniPacket {
+    packet: RequestPacket,
+    attribute: TupUniAttribute,
+}
+
+impl UniPacket {
+    pub fn new(version: ProtocolVersion) -> Self {
+        UniPacket {
+            packet: RequestPacket {
+                version: i16::from(version.value()),
+                ..Default::default()
+            },
+            attribute: TupUniAttribute::new(version),
+        }
+    }
+
+    pub fn servant_name(&self) -> &str {
+        &self.packet.servant_name
+    }
+
+    pub fn set_servant_name(&mut self, servant_name: &str) {
+        self.packet.servant_name = servant_name.to_string();
+    }
+
+    pub fn func_name(&self) -> &str {
+        &self.packet.func_name
+    }
+
+    pub fn set_func_name(&mut self, func_name: &str) {
+        self.packet.func_name = func_name.to_string();
+    }
+
+    pub fn request_id(&self) -> i32 {
+        self.packet.request_id
+    }
+
+    pub fn set_request_id(&mut self, request_id: i32) {
+        self.packet.request_id = request_id;
+    }
+
+    pub fn version(&self) -> ProtocolVersion {
+        self.attribute.version()
+    }
+
+    // timeout、context 等其余 field，sBuffer 与 iVersion 在 encode 时覆盖
+    pub fn packet(&self) -> &RequestPacket {
+        &self.packet
+    }
+
+    pub fn packet_mut(&mut self) -> &mut RequestPacket {
+        &mut self.packet
+    }
+
+    pub fn attribute(&self) -> &TupUniAttribute {
+        &self.attribute
+    }
+
+    pub fn attribute_mut(&mut self) -> &mut TupUniAttribute {
+        &mut self.attribute
+    }
+
+    pub fn read<T>(&self, name: &str, is_require: bool, default_value: T) -> Result<T, DecodeErr>
+    where
+        T: DecodeTars + ClassName,
+    {
+        self.attribute.read(name, is_require, default_value)
+    }
+
+    pub fn write<T>(&mut self, name: &str, value: &T) -> Result<(), EncodeErr>
+    where
+        T: EncodeTars + ClassName + ?Sized,
+    {
+        self.attribute.write(name, value)
+    }
+
+    // 与 Tars-Java 的 UniPacket.createResponse 一致，沿用 request id、版本、servant 与 func
+    pub fn create_response(&self) -> Self {
+        let mut response = UniPacket::new(self.version());
+        response.packet.request_id = self.packet.request_id;
+        response.packet.servant_name = self.packet.servant_name.clone();
+        response.packet.func_name = self.packet.func_name.clone();
+        response
+    }
+
+    pub fn encode(&self) -> Result<Bytes, EncodeErr> {
+        let mut packet = self.packet.clone();
+        packet.version = i16::from(self.version().value());
+        packet.buffer = self.attribute.to_bytes()?;
+
+        let mut encoder = TarsEncoder::new();
+        packet._encode_to(&mut encoder)?;
+        let body = encoder.to_bytes();
+
+        let len = body.len() + 4;
+        if len > i32::max_value() as usize {
+            return Err(EncodeErr::DataTooBigErr);
+        }
+        let mut buf = BytesMut::with_capacity(len);
+        buf.put_u32_be(len as u32);
+        buf.extend_from_slice(&body);
+        Ok(buf.freeze())
+    }
+
+    // buf 必须恰好是一个完整的包
+    pub fn decode(buf: &Bytes) -> Result<Self, DecodeErr> {
+        if buf.len() < 4 {
+            return Err(DecodeErr::NoEnoughDataErr);
+        }
+        let len = buf.slice_to(4).into_buf().get_u32_be() as usize;
+        if len < 4 {
+            return Err(DecodeErr::InvalidPacketLengthErr);
+        }
+        if buf.len() < len {
+            return Err(DecodeErr::NoEnoughDataErr);
+        }
+        if buf.len() > len {
+            return Err(DecodeErr::TrailingDataErr);
+        }
+
+        let mut decoder = TarsDecoder::from(&buf.slice_from(4));
+        let packet = RequestPacket::_decode_from(&mut decoder)?;
+        let version = match packet.version {
+            2 => ProtocolVersion::TupSimple,
+            3 => ProtocolVersion::TupComplex,
+            _ => return Err(DecodeErr::UnsupportTupVersionErr),
+        };
+        let attribute = TupUniAttribute::from_bytes(&packet.buffer, version)?;
+        Ok(UniPacket { packet, attribute })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uni_packet() {
+        let mut req = UniPacket::new(ProtocolVersion::TupComplex);
+        req.set_servant_name("Test.HelloServer.HelloObj");
+        req.set_func_name("hello");
+        req.set_request_id(42);
+        req.packet_mut().timeout = 3000;
+        req.write("name", &"foo".to_string()).unwrap();
+        let buf = req.encode().unwrap();
+
+        assert_eq!(&buf[..4], &[0, 0, 0, buf.len() as u8][..]);
+        let de = UniPacket::decode(&buf).unwrap();
+        assert_eq!(de.version(), ProtocolVersion::TupComplex);
+        assert_eq!(de.packet().version, 3);
+        assert_eq!(de.servant_name(), "Test.HelloServer.HelloObj");
+        assert_eq!(de.func_name(), "hello");
+        assert_eq!(de.request_id(), 42);
+        assert_eq!(de.packet().timeout, 3000);
+        assert_eq!(de.read("name", true, String::new()), Ok("foo".to_string()));
+
+        let mut resp = de.create_response();
+        assert_eq!(resp.request_id(), 42);
+        assert_eq!(resp.version(), ProtocolVersion::TupComplex);
+        assert_eq!(resp.servant_name(), "Test.HelloServer.HelloObj");
+        assert_eq!(resp.func_name(), "hello");
+        assert_eq!(resp.packet().timeout, 0);
+        resp.write("", &0i32).unwrap();
+        let resp = UniPacket::decode(&resp.encode().unwrap()).unwrap();
+        assert_eq!(resp.read("", true, -1i32), Ok(0));
+    }
+
+    #[test]
+    fn test_uni_packet_err() {
+        let buf = UniPacket::new(ProtocolVersion::TupSimple).encode().unwrap();
+        assert!(UniPacket::decode(&buf).is_ok());
+        assert_eq!(
+            UniPacket::decode(&buf.slice_to(buf.len() - 1)),
+            Err(DecodeErr::NoEnoughDataErr)
+        );
+        let mut long = BytesMut::from(&buf[..]);
+        long.extend_from_slice(b"\x00");
+        assert_eq!(
+            UniPacket::decode(&long.freeze()),
+            Err(DecodeErr::TrailingDataErr)
+        );
+        assert_eq!(
+            UniPacket::decode(&Bytes::from(&b"\x00\x00\x00\x02"[..])),
+            Err(DecodeErr::InvalidPacketLengthErr)
+        );
+        assert_eq!(
+            UniPacket::decode(&Bytes::from(&b"\x00\x00"[..])),
+            Err(DecodeErr::NoEnoughDataErr)
+        );
+
+        // iVersion 为 1 (tars) 不是 tup 包
+        let packet = RequestPacket {
+            version: 1,
+            ..Default::default()
+        };
+        let mut encoder = TarsEncoder::new();
+        packet._encode_to(&mut encoder).unwrap();
+        let body = encoder.to_bytes();
+        let mut buf = BytesMut::new();
+        buf.put_u32_be(body.len() as u32 + 4);
+        buf.extend_from_slice(&body);
+        assert_eq!(
+            UniPacket::decode(&buf.freeze()),
+            Err(DecodeErr::UnsupportTupVersionErr)
+        );
+    }
+}
@@ -0,0 +1,136 @@
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+    Data, DeriveInput, Field, Fields, GenericArgument, Lit, Meta, NestedMeta, PathArguments, Type,
+};
+
+// 为具名字段的 struct 实现 tars_stream 的 TupAttribute，字段名即 key
+// 可用 #[tup(rename = "sName")] 指定 key，Option<T> 字段为可选
+#[proc_macro_derive(TupAttribute, attributes(tup))]
+pub fn derive_tup_attribute(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "TupAttribute only supports structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "TupAttribute only supports structs with named fields",
+            ))
+        }
+    };
+
+    let mut writes = vec![];
+    let mut reads = vec![];
+    for field in fields.iter() {
+        let ident = &field.ident;
+        let key = key_name(field)?;
+        match option_inner(&field.ty) {
+            Some(inner) => {
+                writes.push(quote! {
+                    if let Some(ref v) = self.#ident {
+                        uni.write(#key, v)?;
+                    }
+                });
+                reads.push(quote! {
+                    #ident: uni.try_read::<#inner>(#key)?
+                });
+            }
+            None => {
+                let ty = &field.ty;
+                writes.push(quote! {
+                    uni.write(#key, &self.#ident)?;
+                });
+                reads.push(quote! {
+                    #ident: match uni.try_read::<#ty>(#key)? {
+                        Some(v) => v,
+                        None => return Err(::tars_stream::errors::DecodeErr::TupKeyNotFoundErr),
+                    }
+                });
+            }
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tars_stream::tup_uni_attribute::TupAttribute for #name #ty_generics #where_clause {
+            fn _write_to(
+                &self,
+                uni: &mut ::tars_stream::tup_uni_attribute::TupUniAttribute,
+            ) -> ::std::result::Result<(), ::tars_stream::errors::EncodeErr> {
+                #(#writes)*
+                Ok(())
+            }
+
+            fn _read_from(
+                uni: &::tars_stream::tup_uni_attribute::TupUniAttribute,
+            ) -> ::std::result::Result<Self, ::tars_stream::errors::DecodeErr> {
+                Ok(#name {
+                    #(#reads,)*
+                })
+            }
+        }
+    })
+}
+
+fn key_name(field: &Field) -> syn::Result<String> {
+    let mut key = field.ident.as_ref().map(|i| i.to_string()).unwrap_or_default();
+    for attr in field.attrs.iter().filter(|a| a.path.is_ident("tup")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[tup(rename = \"...\")]")),
+        };
+        for nested in list.nested.iter() {
+            match *nested {
+                NestedMeta::Meta(Meta::NameValue(ref nv)) if nv.path.is_ident("rename") => {
+                    match nv.lit {
+                        Lit::Str(ref s) => key = s.value(),
+                        ref lit => return Err(syn::Error::new_spanned(lit, "rename expects a string")),
+                    }
+                }
+                ref other => return Err(syn::Error::new_spanned(other, "unknown tup attribute")),
+            }
+        }
+    }
+    Ok(key)
+}
+
+// Option<T> 返回 T
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let path = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+    let seg = path.segments.last()?;
+    if seg.ident != "Option" {
+        return None;
+    }
+    match seg.arguments {
+        PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => match args.args[0] {
+            GenericArgument::Type(ref inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
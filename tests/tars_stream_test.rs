@@ -2,6 +2,8 @@
 extern crate bytes;
 extern crate rand;
 extern crate tars_stream;
+#[macro_use]
+extern crate tars_stream_derive;
 extern crate uuid;
 
 use bytes::Bytes;
@@ -523,3 +525,75 @@ fn test_encode_decode_tup() {
     let de_fake_value = uni.read(&fake_key, false, fake_default.clone()).unwrap();
     assert_eq!(de_fake_value, fake_default);
 }
+
+#[cfg(feature = "json")]
+impl TarsSchemaOf for TestStruct {
+    fn _schema() -> TarsSchema {
+        TarsSchema::Struct(vec![
+            SchemaField::new(0, "a", i8::_schema()),
+            SchemaField::new(1, "b", u16::_schema()),
+            SchemaField::new(2, "v1", <Vec<u8>>::_schema()),
+            SchemaField::new(3, "c", String::_schema()),
+            SchemaField::new(4, "v2", <Vec<i8>>::_schema()),
+            SchemaField::new(5, "v3", <Vec<bool>>::_schema()),
+        ])
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_struct_json() {
+    let ts = TestStruct::random_for_test();
+    let json = to_json(&ts).unwrap();
+    assert_eq!(json["c"], ts.c.as_str());
+    assert_eq!(json["v3"].as_array().unwrap().len(), ts.v3.len());
+
+    let de_ts: TestStruct = from_json(&json).unwrap();
+    assert_eq!(de_ts, ts);
+}
+
+#[derive(Clone, Debug, PartialEq, TupAttribute)]
+struct TestTupRequest {
+    #[tup(rename = "sName")]
+    name: String,
+    ids: Vec<i32>,
+    test: TestStruct,
+    comment: Option<String>,
+}
+
+#[test]
+fn test_tup_attribute_derive() {
+    for &version in [ProtocolVersion::TupSimple, ProtocolVersion::TupComplex].iter() {
+        let req = TestTupRequest {
+            name: "foo".to_string(),
+            ids: vec![1, 2],
+            test: TestStruct::random_for_test(),
+            comment: None,
+        };
+        let mut uni = TupUniAttribute::new(version);
+        uni.write_fields(&req).unwrap();
+        assert_eq!(uni.keys(), vec!["ids", "sName", "test"]);
+        assert_eq!(
+            uni.read("sName", true, String::new()),
+            Ok("foo".to_string())
+        );
+
+        let buf = uni.to_bytes().unwrap();
+        let de = TupUniAttribute::from_bytes(&buf, version).unwrap();
+        assert_eq!(de.read_fields::<TestTupRequest>(), Ok(req.clone()));
+
+        let req = TestTupRequest {
+            comment: Some("bar".to_string()),
+            ..req
+        };
+        let mut uni = TupUniAttribute::new(version);
+        uni.write_fields(&req).unwrap();
+        assert_eq!(uni.read_fields::<TestTupRequest>(), Ok(req));
+
+        uni.remove("ids");
+        assert_eq!(
+            uni.read_fields::<TestTupRequest>(),
+            Err(DecodeErr::TupKeyNotFoundErr)
+        );
+    }
+}